@@ -0,0 +1,126 @@
+use crate::derived::psychrometrics::{relative_humidity, vapor_pressure};
+use crate::model::Record;
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+
+fn to_fahrenheit(celsius: f64) -> f64 {
+    celsius * 9.0 / 5.0 + 32.0
+}
+
+fn to_celsius(fahrenheit: f64) -> f64 {
+    (fahrenheit - 32.0) * 5.0 / 9.0
+}
+
+/// NWS heat index in °C, using the Rothfusz regression with the NWS low and high humidity
+/// adjustments, and Steadman's simple formula where it gives less than 80 °F.
+/// `None` below 80 °F (26.7 °C), where the index is not defined.
+pub fn heat_index(temperature: f64, relative_humidity: f64) -> Option<f64> {
+    let t = to_fahrenheit(temperature);
+    let rh = relative_humidity;
+    if t < 80.0 {
+        return None;
+    }
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    if (simple + t) / 2.0 < 80.0 {
+        return Some(to_celsius(simple));
+    }
+    let mut hi = -42.379 + 2.049_015_23 * t + 10.143_331_27 * rh
+        - 0.224_755_41 * t * rh
+        - 6.837_83e-3 * t * t
+        - 5.481_717e-2 * rh * rh
+        + 1.228_74e-3 * t * t * rh
+        + 8.5282e-4 * t * rh * rh
+        - 1.99e-6 * t * t * rh * rh;
+    if rh < 13.0 && t <= 112.0 {
+        hi -= ((13.0 - rh) / 4.0) * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && t <= 87.0 {
+        hi += ((rh - 85.0) / 10.0) * ((87.0 - t) / 5.0);
+    }
+    Some(to_celsius(hi))
+}
+
+/// NWS (2001) wind chill in °C for a wind speed in m/s at 10 m.
+/// `None` above 10 °C (50 °F) or below 1.34 m/s (3 mph), where the index is not defined.
+pub fn wind_chill(temperature: f64, wind_speed: f64) -> Option<f64> {
+    if temperature > 10.0 || wind_speed < 1.34 {
+        return None;
+    }
+    let v = (wind_speed * 3.6).powf(0.16);
+    Some(13.12 + 0.6215 * temperature - 11.37 * v + 0.3965 * temperature * v)
+}
+
+/// Environment Canada humidex. `None` below 20 °C, where Environment Canada does not report
+/// it. Dry air with a vapor pressure below 10 hPa gives a humidex below the temperature.
+pub fn humidex(temperature: f64, dew_point: f64) -> Option<f64> {
+    if temperature < 20.0 {
+        return None;
+    }
+    let e = 6.11 * (5417.7530 * (1.0 / 273.16 - 1.0 / (273.15 + dew_point))).exp();
+    Some(temperature + 0.5555 * (e - 10.0))
+}
+
+/// Steadman's apparent temperature in °C for shade, as used by the Australian Bureau of
+/// Meteorology, for a wind speed in m/s at 10 m.
+pub fn steadman_apparent_temperature(temperature: f64, dew_point: f64, wind_speed: f64) -> f64 {
+    temperature + 0.33 * vapor_pressure(dew_point) - 0.70 * wind_speed - 4.00
+}
+
+/// The apparent temperature indices of a `Record`, each `None` when an input is missing or
+/// the conditions are outside the range the index is defined for.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct ApparentTemperature {
+    pub heat_index: Option<f64>,
+    pub wind_chill: Option<f64>,
+    pub humidex: Option<f64>,
+    pub apparent_temperature: Option<f64>,
+}
+
+impl ApparentTemperature {
+    pub fn from_record(record: &Record) -> ApparentTemperature {
+        let temperature = match record.tmp().air_temperature() {
+            Some(t) => t,
+            None => return ApparentTemperature::default(),
+        };
+        let dew_point = record.dew().dew_point_temperature();
+        let wind_speed = record.wnd().speed_rate();
+        ApparentTemperature {
+            heat_index: dew_point
+                .and_then(|td| heat_index(temperature, relative_humidity(temperature, td))),
+            wind_chill: wind_speed.and_then(|ws| wind_chill(temperature, ws)),
+            humidex: dew_point.and_then(|td| humidex(temperature, td)),
+            apparent_temperature: dew_point
+                .zip(wind_speed)
+                .map(|(td, ws)| steadman_apparent_temperature(temperature, td, ws)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heat_index() {
+        // NWS table: 90 °F at 70% is 106 °F
+        let hi = heat_index(to_celsius(90.0), 70.0).unwrap();
+        assert!((to_fahrenheit(hi) - 106.0).abs() < 0.5);
+        assert_eq!(heat_index(25.0, 70.0), None);
+    }
+
+    #[test]
+    fn test_wind_chill_and_humidex() {
+        // Environment Canada table: -10 °C with 20 km/h wind is -18
+        let wc = wind_chill(-10.0, 20.0 / 3.6).unwrap();
+        assert!((wc + 17.9).abs() < 0.1);
+        assert_eq!(wind_chill(15.0, 10.0), None);
+        assert_eq!(wind_chill(-10.0, 0.5), None);
+
+        let h = humidex(30.0, 15.0).unwrap();
+        assert!((h - 34.0).abs() < 0.1);
+        assert_eq!(humidex(15.0, 10.0), None);
+        // a dew point below freezing, 4.2 hPa, lowers it
+        let h = humidex(25.0, -5.0).unwrap();
+        assert!((h - 21.8).abs() < 0.1);
+    }
+}
@@ -0,0 +1,315 @@
+use crate::fields::optional::runway_visual_range::ED1;
+use crate::model::Record;
+use crate::station::id::StationId;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A runway and the direction in degrees it points to.
+///
+/// ISD wind directions are relative to true north while runway numbers follow magnetic
+/// headings, so a heading derived from the designator is off by the local declination.
+/// Give true headings in a `RunwayTable` where that matters.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Runway {
+    pub designator: String,
+    pub heading: f64,
+}
+
+impl Runway {
+    pub fn new(designator: &str, heading: f64) -> Runway {
+        Runway {
+            designator: designator.to_string(),
+            heading: heading.rem_euclid(360.0),
+        }
+    }
+
+    /// The runway of an `ED1` runway visual range group, `None` when its direction is missing.
+    pub fn from_ed1(ed1: &ED1) -> Option<Runway> {
+        let heading = ed1.direction_angle()?;
+        let number = (heading / 10.0).round() as u32;
+        if !(1..=36).contains(&number) {
+            return None;
+        }
+        let side = match ed1.designator_code() {
+            code @ ("L" | "C" | "R") => code,
+            _ => "",
+        };
+        Some(Runway::new(&format!("{:02}{}", number, side), heading))
+    }
+}
+
+impl FromStr for Runway {
+    type Err = &'static str;
+
+    /// Parses a designator such as `09`, `27L` or `36R`, with the heading taken from the number.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (number, side) = match s.strip_suffix(['L', 'C', 'R']) {
+            Some(number) => (number, &s[number.len()..]),
+            None => (s, ""),
+        };
+        let number: u32 = number.parse().map_err(|_| "invalid runway number")?;
+        if !(1..=36).contains(&number) {
+            return Err("runway number must be between 01 and 36");
+        }
+        Ok(Runway::new(
+            &format!("{:02}{}", number, side),
+            number as f64 * 10.0,
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+struct RunwayRow {
+    #[serde(rename = "STATION")]
+    station: StationId,
+    #[serde(rename = "RUNWAY")]
+    runway: String,
+    #[serde(rename = "HEADING")]
+    heading: Option<f64>,
+}
+
+/// The runways of each station.
+#[derive(Debug, Clone, Default)]
+pub struct RunwayTable {
+    runways: BTreeMap<StationId, Vec<Runway>>,
+}
+
+impl RunwayTable {
+    pub fn new() -> RunwayTable {
+        RunwayTable::default()
+    }
+
+    /// Reads a CSV file with `STATION`, `RUNWAY` and `HEADING` columns. Rows without a
+    /// heading take it from the runway number.
+    pub fn from_reader<R: io::Read>(reader: R) -> Result<RunwayTable, Box<dyn Error>> {
+        let mut table = RunwayTable::new();
+        for row in csv::Reader::from_reader(reader).deserialize() {
+            let row: RunwayRow = row?;
+            let runway = match row.heading {
+                Some(heading) => Runway::new(row.runway.trim(), heading),
+                None => row.runway.parse()?,
+            };
+            table.insert(row.station, runway);
+        }
+        Ok(table)
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<RunwayTable, Box<dyn Error>> {
+        RunwayTable::from_reader(File::open(path)?)
+    }
+
+    pub fn insert(&mut self, station: StationId, runway: Runway) {
+        self.runways.entry(station).or_default().push(runway);
+    }
+
+    pub fn get(&self, station: &StationId) -> &[Runway] {
+        self.runways.get(station).map_or(&[], |r| r.as_slice())
+    }
+
+    /// The runways of the record's station, or the runway of its `ED1` group when the
+    /// station is not in the table.
+    pub fn for_record(&self, record: &Record) -> Vec<Runway> {
+        match record.station().map(|s| self.get(s)) {
+            Some(runways) if !runways.is_empty() => runways.to_vec(),
+            _ => record
+                .ed1()
+                .and_then(Runway::from_ed1)
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// Headwind and crosswind components in the units of `speed` for a wind from `direction`
+/// degrees on a runway pointing to `heading` degrees. A negative headwind is a tailwind
+/// and a positive crosswind blows from the right.
+pub fn components(direction: f64, speed: f64, heading: f64) -> (f64, f64) {
+    let angle = (direction - heading).to_radians();
+    (speed * angle.cos(), speed * angle.sin())
+}
+
+/// The gust speed of a record in m/s, from `OC1`, else the highest maximum gust or
+/// maximum instantaneous speed in `OA1`-`OA5` over at most an hour. Maxima over longer
+/// periods are not gusts at the time of the report.
+pub fn gust_speed(record: &Record) -> Option<f64> {
+    record.oc1().and_then(|oc1| oc1.speed_rate()).or_else(|| {
+        record
+            .oax()
+            .filter(|oa| matches!(oa.type_code(), "3" | "4"))
+            .filter(|oa| oa.period_quantity().is_some_and(|period| period <= 1.0))
+            .filter_map(|oa| oa.speed_rate())
+            .reduce(f64::max)
+    })
+}
+
+/// Limits in m/s, applied to both the steady wind and the gusts.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct CrosswindLimits {
+    pub crosswind: f64,
+    pub tailwind: f64,
+}
+
+impl Default for CrosswindLimits {
+    /// 20 kt crosswind and 10 kt tailwind.
+    fn default() -> CrosswindLimits {
+        CrosswindLimits {
+            crosswind: 10.3,
+            tailwind: 5.1,
+        }
+    }
+}
+
+/// The wind components along a runway in m/s.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RunwayWind {
+    pub runway: Runway,
+    pub headwind: f64,
+    pub crosswind: f64,
+    pub gust_headwind: Option<f64>,
+    pub gust_crosswind: Option<f64>,
+    pub crosswind_exceeded: bool,
+    pub tailwind_exceeded: bool,
+}
+
+impl RunwayWind {
+    /// `None` when the wind is variable or missing, since there is no direction to resolve.
+    /// Calm winds have zero components.
+    pub fn from_record(
+        record: &Record,
+        runway: &Runway,
+        limits: &CrosswindLimits,
+    ) -> Option<RunwayWind> {
+        let wind = record.wnd();
+        let (direction, speed) = if wind.is_calm() {
+            (runway.heading, 0.0)
+        } else if wind.is_variable() {
+            return None;
+        } else {
+            (wind.direction_angle()? as f64, wind.speed_rate()?)
+        };
+        let (headwind, crosswind) = components(direction, speed, runway.heading);
+        let gust = gust_speed(record)
+            .filter(|gust| *gust > speed)
+            .map(|gust| components(direction, gust, runway.heading));
+        let worst_headwind = gust.map_or(headwind, |(h, _)| h.min(headwind));
+        let worst_crosswind = gust.map_or(crosswind.abs(), |(_, c)| c.abs().max(crosswind.abs()));
+        Some(RunwayWind {
+            runway: runway.clone(),
+            headwind,
+            crosswind,
+            gust_headwind: gust.map(|(h, _)| h),
+            gust_crosswind: gust.map(|(_, c)| c),
+            crosswind_exceeded: worst_crosswind > limits.crosswind,
+            tailwind_exceeded: -worst_headwind > limits.tailwind,
+        })
+    }
+
+    pub fn is_within_limits(&self) -> bool {
+        !self.crosswind_exceeded && !self.tailwind_exceeded
+    }
+}
+
+/// The runway winds of one observation.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RunwaySuitability {
+    pub station: Option<StationId>,
+    pub date: NaiveDateTime,
+    pub runways: Vec<RunwayWind>,
+}
+
+impl RunwaySuitability {
+    /// Whether any runway is within limits.
+    pub fn is_usable(&self) -> bool {
+        self.runways.iter().any(RunwayWind::is_within_limits)
+    }
+
+    /// The usable runway with the most headwind.
+    pub fn best_runway(&self) -> Option<&RunwayWind> {
+        self.runways
+            .iter()
+            .filter(|r| r.is_within_limits())
+            .max_by(|a, b| a.headwind.total_cmp(&b.headwind))
+    }
+}
+
+/// The runway winds of each record, in date order. Records without a known runway or a
+/// resolvable wind are left out.
+pub fn suitability_timeline<'a, I>(
+    records: I,
+    table: &RunwayTable,
+    limits: &CrosswindLimits,
+) -> Vec<RunwaySuitability>
+where
+    I: IntoIterator<Item = &'a Record>,
+{
+    let mut timeline: Vec<RunwaySuitability> = records
+        .into_iter()
+        .filter_map(|record| {
+            let runways: Vec<RunwayWind> = table
+                .for_record(record)
+                .iter()
+                .filter_map(|runway| RunwayWind::from_record(record, runway, limits))
+                .collect();
+            if runways.is_empty() {
+                return None;
+            }
+            Some(RunwaySuitability {
+                station: record.station().copied(),
+                date: record.date(),
+                runways,
+            })
+        })
+        .collect();
+    timeline.sort_by_key(|s| s.date);
+    timeline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::records;
+
+    #[test]
+    fn test_runway_designator() {
+        let runway: Runway = "09L".parse().unwrap();
+        assert_eq!(runway.designator, "09L");
+        assert_eq!(runway.heading, 90.0);
+        assert_eq!("36".parse::<Runway>().unwrap().heading, 0.0);
+        assert!("37".parse::<Runway>().is_err());
+        assert!("00R".parse::<Runway>().is_err());
+    }
+
+    #[test]
+    fn test_gust_speed() {
+        let records = records(&[
+            &[("OC1", "0150,1"), ("OA1", "3,01,0200,1")],
+            &[("OA1", "3,01,0120,1"), ("OA2", "4,01,0140,1")],
+            // the maximum of the last 6 hours
+            &[("OA1", "3,06,0200,1")],
+        ]);
+        assert_eq!(gust_speed(&records[0]), Some(15.0));
+        assert_eq!(gust_speed(&records[1]), Some(14.0));
+        assert_eq!(gust_speed(&records[2]), None);
+    }
+
+    #[test]
+    fn test_components() {
+        // 30 degrees off the nose from the right
+        let (headwind, crosswind) = components(300.0, 10.0, 270.0);
+        assert!((headwind - 8.660).abs() < 1e-3);
+        assert!((crosswind - 5.0).abs() < 1e-9);
+        // from the left, behind
+        let (headwind, crosswind) = components(30.0, 10.0, 180.0);
+        assert!((headwind + 8.660).abs() < 1e-3);
+        assert!((crosswind + 5.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::model::Record;
+use crate::station::id::StationId;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// FAA flight categories, ordered from the most to the least restrictive.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum FlightCategory {
+    /// Ceiling below 500 ft or visibility below 1 statute mile.
+    Lifr,
+    /// Ceiling 500 to below 1000 ft or visibility 1 to below 3 statute miles.
+    Ifr,
+    /// Ceiling 1000 to 3000 ft or visibility 3 to 5 statute miles.
+    Mvfr,
+    /// Ceiling above 3000 ft and visibility above 5 statute miles.
+    Vfr,
+}
+
+// ISD stores heights and distances converted to whole meters, so the limits are the
+// rounded values that the converted feet and statute miles land on.
+const CEILING_500_FT: i32 = 152;
+const CEILING_1000_FT: i32 = 305;
+const CEILING_3000_FT: i32 = 914;
+const VISIBILITY_1_SM: i32 = 1609;
+const VISIBILITY_3_SM: i32 = 4828;
+const VISIBILITY_5_SM: i32 = 8047;
+
+impl FlightCategory {
+    /// The category for a ceiling height in meters, 22000 meaning unlimited.
+    pub fn from_ceiling(height: i32) -> FlightCategory {
+        match height {
+            h if h < CEILING_500_FT => FlightCategory::Lifr,
+            h if h < CEILING_1000_FT => FlightCategory::Ifr,
+            h if h <= CEILING_3000_FT => FlightCategory::Mvfr,
+            _ => FlightCategory::Vfr,
+        }
+    }
+
+    /// The category for a visibility in meters.
+    pub fn from_visibility(distance: i32) -> FlightCategory {
+        match distance {
+            d if d < VISIBILITY_1_SM => FlightCategory::Lifr,
+            d if d < VISIBILITY_3_SM => FlightCategory::Ifr,
+            d if d <= VISIBILITY_5_SM => FlightCategory::Mvfr,
+            _ => FlightCategory::Vfr,
+        }
+    }
+
+    /// The more restrictive of the ceiling and visibility categories. A CAVOK report is VFR.
+    /// `None` when either the ceiling or the visibility is missing.
+    pub fn from_record(record: &Record) -> Option<FlightCategory> {
+        if record.cig().cavok() {
+            return Some(FlightCategory::Vfr);
+        }
+        let ceiling = FlightCategory::from_ceiling(record.cig().height()?);
+        let visibility = FlightCategory::from_visibility(record.vis().distance()?);
+        Some(ceiling.min(visibility))
+    }
+}
+
+/// A change of flight category at a station.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CategoryTransition {
+    pub date: NaiveDateTime,
+    /// `None` for the first classified observation.
+    pub from: Option<FlightCategory>,
+    pub to: FlightCategory,
+}
+
+/// The flight category changes of each station, in chronological order. Observations
+/// that cannot be classified do not end the current category.
+pub fn transitions_by_station<'a, I>(records: I) -> BTreeMap<StationId, Vec<CategoryTransition>>
+where
+    I: IntoIterator<Item = &'a Record>,
+{
+    let mut observations: BTreeMap<StationId, Vec<(NaiveDateTime, FlightCategory)>> =
+        BTreeMap::new();
+    for record in records {
+        if let (Some(station), Some(category)) =
+            (record.station(), FlightCategory::from_record(record))
+        {
+            observations
+                .entry(*station)
+                .or_default()
+                .push((record.date(), category));
+        }
+    }
+
+    observations
+        .into_iter()
+        .map(|(station, mut categories)| {
+            categories.sort_by_key(|(date, _)| *date);
+            let mut transitions: Vec<CategoryTransition> = Vec::new();
+            for (date, category) in categories {
+                let from = transitions.last().map(|t| t.to);
+                if from != Some(category) {
+                    transitions.push(CategoryTransition {
+                        date,
+                        from,
+                        to: category,
+                    });
+                }
+            }
+            (station, transitions)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::records;
+
+    #[test]
+    fn test_flight_category_limits() {
+        // ISD values for 500, 1000 and 3000 ft, and for 1, 3 and 5 statute miles
+        assert_eq!(FlightCategory::from_ceiling(152), FlightCategory::Ifr);
+        assert_eq!(FlightCategory::from_ceiling(150), FlightCategory::Lifr);
+        assert_eq!(FlightCategory::from_ceiling(305), FlightCategory::Mvfr);
+        assert_eq!(FlightCategory::from_ceiling(914), FlightCategory::Mvfr);
+        assert_eq!(FlightCategory::from_ceiling(22000), FlightCategory::Vfr);
+        assert_eq!(FlightCategory::from_visibility(1609), FlightCategory::Ifr);
+        assert_eq!(FlightCategory::from_visibility(4828), FlightCategory::Mvfr);
+        assert_eq!(FlightCategory::from_visibility(8047), FlightCategory::Mvfr);
+        assert_eq!(FlightCategory::from_visibility(16093), FlightCategory::Vfr);
+    }
+
+    #[test]
+    fn test_flight_category_from_record() {
+        let records = records(&[
+            // CAVOK with a missing ceiling and visibility
+            &[("CIG", "99999,9,9,Y"), ("VIS", "999999,9,9,9")],
+            // unlimited ceiling in 2 statute miles of visibility
+            &[("DATE", "2020-01-01T01:51:00"), ("VIS", "003219,1,9,9")],
+            // missing ceiling
+            &[("DATE", "2020-01-01T02:51:00"), ("CIG", "99999,9,9,N")],
+            &[("DATE", "2020-01-01T03:51:00"), ("CIG", "00244,1,9,N")],
+            &[("DATE", "2020-01-01T04:51:00")],
+        ]);
+        let categories: Vec<Option<FlightCategory>> =
+            records.iter().map(FlightCategory::from_record).collect();
+        assert_eq!(
+            categories,
+            vec![
+                Some(FlightCategory::Vfr),
+                Some(FlightCategory::Ifr),
+                None,
+                Some(FlightCategory::Ifr),
+                Some(FlightCategory::Vfr),
+            ]
+        );
+
+        // the unclassified observation doesn't end the IFR spell
+        let transitions = &transitions_by_station(&records)[records[0].station().unwrap()];
+        assert_eq!(transitions.len(), 3);
+        assert_eq!(transitions[0].from, None);
+        assert_eq!(
+            (transitions[1].from, transitions[1].to),
+            (Some(FlightCategory::Vfr), FlightCategory::Ifr)
+        );
+        assert_eq!(transitions[1].date, records[1].date());
+        assert_eq!(
+            (transitions[2].from, transitions[2].to),
+            (Some(FlightCategory::Ifr), FlightCategory::Vfr)
+        );
+        assert_eq!(transitions[2].date, records[4].date());
+    }
+}
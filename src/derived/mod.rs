@@ -0,0 +1,7 @@
+pub mod apparent_temperature;
+pub mod crosswind;
+pub mod pressure;
+pub mod psychrometrics;
+pub mod wet_bulb;
+pub mod wind;
+pub mod flight_category;
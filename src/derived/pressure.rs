@@ -0,0 +1,191 @@
+use crate::derived::psychrometrics::vapor_pressure;
+use crate::model::Record;
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+
+/// ICAO standard atmosphere sea level pressure in hPa.
+pub const STANDARD_PRESSURE: f64 = 1013.25;
+/// ICAO standard atmosphere sea level temperature in Kelvin.
+pub const STANDARD_TEMPERATURE: f64 = 288.15;
+/// ICAO standard atmosphere sea level density in kg/m³.
+pub const STANDARD_DENSITY: f64 = 1.225;
+/// Standard atmosphere lapse rate in K/m.
+const LAPSE_RATE: f64 = 0.0065;
+/// `R L / g`, the exponent of the standard atmosphere pressure-height relation.
+const N: f64 = 0.190284;
+const R_DRY: f64 = 287.058;
+const R_VAPOR: f64 = 461.495;
+
+/// Reduces sea level pressure in hPa to the pressure at `elevation` meters, assuming a
+/// standard 6.5 K/km lapse rate below the station. When `temperature` is known it is used
+/// as the station temperature, otherwise the standard atmosphere is assumed.
+pub fn sea_level_to_station_pressure(
+    sea_level_pressure: f64,
+    elevation: f64,
+    temperature: Option<f64>,
+) -> f64 {
+    let lapse = LAPSE_RATE * elevation;
+    let t = temperature.map_or(STANDARD_TEMPERATURE - lapse, |t| t + 273.15);
+    sea_level_pressure * (1.0 - lapse / (t + lapse)).powf(1.0 / N)
+}
+
+/// The inverse of `sea_level_to_station_pressure`.
+pub fn station_to_sea_level_pressure(
+    station_pressure: f64,
+    elevation: f64,
+    temperature: Option<f64>,
+) -> f64 {
+    let lapse = LAPSE_RATE * elevation;
+    let t = temperature.map_or(STANDARD_TEMPERATURE - lapse, |t| t + 273.15);
+    station_pressure / (1.0 - lapse / (t + lapse)).powf(1.0 / N)
+}
+
+/// Altimeter setting in hPa from station pressure in hPa and elevation in meters (NWS formula).
+pub fn altimeter_setting(station_pressure: f64, elevation: f64) -> f64 {
+    let p = station_pressure - 0.3;
+    let k = STANDARD_PRESSURE.powf(N) * LAPSE_RATE / STANDARD_TEMPERATURE;
+    p * (1.0 + k * elevation / p.powf(N)).powf(1.0 / N)
+}
+
+/// Station pressure in hPa from the altimeter setting in hPa and elevation in meters (NWS formula).
+pub fn altimeter_to_station_pressure(altimeter_setting: f64, elevation: f64) -> f64 {
+    let k = STANDARD_PRESSURE.powf(N) * LAPSE_RATE / STANDARD_TEMPERATURE;
+    (altimeter_setting.powf(N) - k * elevation).powf(1.0 / N) + 0.3
+}
+
+/// The height in meters of the standard atmosphere level with the given pressure in hPa.
+pub fn pressure_altitude(station_pressure: f64) -> f64 {
+    STANDARD_TEMPERATURE / LAPSE_RATE * (1.0 - (station_pressure / STANDARD_PRESSURE).powf(N))
+}
+
+/// Density of moist air in kg/m³. Without a dew point the air is taken as dry.
+pub fn air_density(station_pressure: f64, temperature: f64, dew_point: Option<f64>) -> f64 {
+    let t = temperature + 273.15;
+    let e = dew_point.map_or(0.0, vapor_pressure);
+    ((station_pressure - e) * 100.0) / (R_DRY * t) + (e * 100.0) / (R_VAPOR * t)
+}
+
+/// The height in meters of the standard atmosphere level with the same air density.
+pub fn density_altitude(station_pressure: f64, temperature: f64, dew_point: Option<f64>) -> f64 {
+    let density = air_density(station_pressure, temperature, dew_point);
+    let exponent = 1.0 / (1.0 / N - 1.0);
+    STANDARD_TEMPERATURE / LAPSE_RATE * (1.0 - (density / STANDARD_DENSITY).powf(exponent))
+}
+
+/// A pressure value and whether it was reported or derived from the others.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct PressureValue {
+    /// Pressure in hPa.
+    pub value: f64,
+    pub derived: bool,
+}
+
+impl PressureValue {
+    fn reported(value: f64) -> PressureValue {
+        PressureValue {
+            value,
+            derived: false,
+        }
+    }
+    fn derived(value: f64) -> PressureValue {
+        PressureValue {
+            value,
+            derived: true,
+        }
+    }
+}
+
+/// The pressures of a `Record`, with any missing one derived from the others, and the
+/// pressure and density altitudes in meters.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Pressures {
+    pub station_pressure: Option<PressureValue>,
+    pub altimeter_setting: Option<PressureValue>,
+    pub sea_level_pressure: Option<PressureValue>,
+    pub pressure_altitude: Option<f64>,
+    pub density_altitude: Option<f64>,
+}
+
+impl Pressures {
+    /// Station pressure is taken from `MA1`, else derived from the `MA1` altimeter setting,
+    /// else reduced from `slp`. The altimeter setting and sea level pressure are then derived
+    /// from station pressure when they were not reported.
+    pub fn from_record(record: &Record) -> Pressures {
+        let elevation = record.elevation();
+        let temperature = record.tmp().air_temperature();
+        let ma1 = record.ma1();
+        let reported_altimeter = ma1.and_then(|ma1| ma1.altimeter_setting());
+        let reported_slp = record.slp().pressure();
+
+        let station_pressure = ma1
+            .and_then(|ma1| ma1.station_pressure())
+            .map(PressureValue::reported)
+            .or_else(|| {
+                reported_altimeter
+                    .map(|a| PressureValue::derived(altimeter_to_station_pressure(a, elevation)))
+            })
+            .or_else(|| {
+                reported_slp.map(|slp| {
+                    PressureValue::derived(sea_level_to_station_pressure(
+                        slp,
+                        elevation,
+                        temperature,
+                    ))
+                })
+            });
+        let ps = station_pressure.map(|p| p.value);
+
+        Pressures {
+            station_pressure,
+            altimeter_setting: reported_altimeter
+                .map(PressureValue::reported)
+                .or_else(|| ps.map(|p| PressureValue::derived(altimeter_setting(p, elevation)))),
+            sea_level_pressure: reported_slp.map(PressureValue::reported).or_else(|| {
+                ps.map(|p| {
+                    PressureValue::derived(station_to_sea_level_pressure(p, elevation, temperature))
+                })
+            }),
+            pressure_altitude: ps.map(pressure_altitude),
+            density_altitude: ps
+                .zip(temperature)
+                .map(|(p, t)| density_altitude(p, t, record.dew().dew_point_temperature())),
+        }
+    }
+}
+
+/// The pressure at the station in hPa, see `Pressures::from_record`.
+pub fn station_pressure(record: &Record) -> Option<f64> {
+    Pressures::from_record(record)
+        .station_pressure
+        .map(|p| p.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_altimeter_setting() {
+        // 29.92 inHg at 1000 ft is 28.86 inHg station pressure
+        let station = altimeter_to_station_pressure(STANDARD_PRESSURE, 304.8);
+        assert!((station / 33.8639 - 28.86).abs() < 0.01);
+        assert!((altimeter_setting(station, 304.8) - STANDARD_PRESSURE).abs() < 0.01);
+        // roughly 12 hPa lost per 100 m near sea level
+        let p = sea_level_to_station_pressure(STANDARD_PRESSURE, 100.0, None);
+        assert!((p - 1001.3).abs() < 0.2);
+        assert!((station_to_sea_level_pressure(p, 100.0, None) - STANDARD_PRESSURE).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_altitudes() {
+        assert!(pressure_altitude(STANDARD_PRESSURE).abs() < 1e-9);
+        // 5000 ft standard pressure is 843.1 hPa
+        assert!((pressure_altitude(843.07) - 1524.0).abs() < 2.0);
+        // density altitude at standard conditions is zero, and rises with heat
+        assert!(density_altitude(STANDARD_PRESSURE, 15.0, None).abs() < 1.0);
+        // 5000 ft field at 30 °C is about 8000 ft density altitude
+        let da = density_altitude(843.07, 30.0, None);
+        assert!((da / 0.3048 - 8000.0).abs() < 200.0);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::derived::pressure::station_pressure;
+use crate::model::Record;
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+
+/// Ratio of the molecular weight of water vapor to dry air.
+pub const EPSILON: f64 = 0.621_98;
+
+/// Saturation vapor pressure over water in hPa, from the Magnus formula with the
+/// coefficients of Alduchov and Eskridge (1996). Valid from -40 °C to 50 °C.
+pub fn saturation_vapor_pressure(temperature: f64) -> f64 {
+    6.1094 * (17.625 * temperature / (temperature + 243.04)).exp()
+}
+
+/// Actual vapor pressure in hPa, which is the saturation vapor pressure at the dew point.
+pub fn vapor_pressure(dew_point: f64) -> f64 {
+    saturation_vapor_pressure(dew_point)
+}
+
+/// Relative humidity in percent.
+pub fn relative_humidity(temperature: f64, dew_point: f64) -> f64 {
+    (100.0 * vapor_pressure(dew_point) / saturation_vapor_pressure(temperature)).min(100.0)
+}
+
+/// The dew point in °C for a temperature and relative humidity in percent, inverting the Magnus formula.
+pub fn dew_point(temperature: f64, relative_humidity: f64) -> f64 {
+    let gamma = (relative_humidity / 100.0).ln() + 17.625 * temperature / (temperature + 243.04);
+    243.04 * gamma / (17.625 - gamma)
+}
+
+/// Mixing ratio in g/kg for a vapor pressure and total pressure in hPa.
+pub fn mixing_ratio(vapor_pressure: f64, pressure: f64) -> f64 {
+    1000.0 * EPSILON * vapor_pressure / (pressure - vapor_pressure)
+}
+
+/// Specific humidity in g/kg for a vapor pressure and total pressure in hPa.
+pub fn specific_humidity(vapor_pressure: f64, pressure: f64) -> f64 {
+    1000.0 * EPSILON * vapor_pressure / (pressure - (1.0 - EPSILON) * vapor_pressure)
+}
+
+/// Moisture variables derived from the temperature and dew point of a `Record`.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Psychrometrics {
+    /// Air temperature in °C.
+    pub temperature: f64,
+    /// Dew point temperature in °C.
+    pub dew_point: f64,
+    /// Temperature minus dew point in °C.
+    pub dew_point_depression: f64,
+    /// Relative humidity in percent.
+    pub relative_humidity: f64,
+    /// Actual vapor pressure in hPa.
+    pub vapor_pressure: f64,
+    /// Saturation vapor pressure in hPa.
+    pub saturation_vapor_pressure: f64,
+    /// Pressure at the station in hPa, see `Pressures::from_record`.
+    pub station_pressure: Option<f64>,
+    /// Mixing ratio in g/kg.
+    pub mixing_ratio: Option<f64>,
+    /// Specific humidity in g/kg.
+    pub specific_humidity: Option<f64>,
+}
+
+impl Psychrometrics {
+    pub fn new(temperature: f64, dew_point: f64, station_pressure: Option<f64>) -> Psychrometrics {
+        let e = vapor_pressure(dew_point);
+        Psychrometrics {
+            temperature,
+            dew_point,
+            dew_point_depression: temperature - dew_point,
+            relative_humidity: relative_humidity(temperature, dew_point),
+            vapor_pressure: e,
+            saturation_vapor_pressure: saturation_vapor_pressure(temperature),
+            station_pressure,
+            mixing_ratio: station_pressure.map(|p| mixing_ratio(e, p)),
+            specific_humidity: station_pressure.map(|p| specific_humidity(e, p)),
+        }
+    }
+
+    /// `None` when the record is missing either the temperature or the dew point.
+    pub fn from_record(record: &Record) -> Option<Psychrometrics> {
+        let temperature = record.tmp().air_temperature()?;
+        let dew_point = record.dew().dew_point_temperature()?;
+        Some(Psychrometrics::new(
+            temperature,
+            dew_point,
+            station_pressure(record),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vapor_pressure() {
+        // 6.11 hPa at freezing, 42.4 hPa at 30 °C
+        assert!((saturation_vapor_pressure(0.0) - 6.11).abs() < 0.01);
+        assert!((saturation_vapor_pressure(30.0) - 42.4).abs() < 0.2);
+        assert_eq!(relative_humidity(20.0, 20.0), 100.0);
+        assert!((relative_humidity(30.0, 20.0) - 55.3).abs() < 0.5);
+        assert!((dew_point(30.0, relative_humidity(30.0, 20.0)) - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_humidity_ratios() {
+        let p = Psychrometrics::new(25.0, 15.0, Some(1013.25));
+        assert!((p.mixing_ratio.unwrap() - 10.6).abs() < 0.1);
+        assert!((p.specific_humidity.unwrap() - 10.5).abs() < 0.1);
+        assert_eq!(p.dew_point_depression, 10.0);
+    }
+}
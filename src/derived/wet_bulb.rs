@@ -0,0 +1,376 @@
+use crate::derived::pressure::{
+    sea_level_to_station_pressure, station_pressure, STANDARD_PRESSURE,
+};
+use crate::derived::psychrometrics::{
+    relative_humidity, saturation_vapor_pressure, vapor_pressure, EPSILON,
+};
+use crate::model::Record;
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use serde::Serialize;
+use std::f64::consts::PI;
+
+/// Wet-bulb temperature in °C from Stull (2011), for a relative humidity in percent at
+/// standard sea level pressure. `None` outside the fitted range of 5-99% and -20-50 °C.
+pub fn stull_wet_bulb(temperature: f64, relative_humidity: f64) -> Option<f64> {
+    let (t, rh) = (temperature, relative_humidity);
+    if !(5.0..=99.0).contains(&rh) || !(-20.0..=50.0).contains(&t) {
+        return None;
+    }
+    Some(
+        t * (0.151977 * (rh + 8.313659).sqrt()).atan() + (t + rh).atan() - (rh - 1.676331).atan()
+            + 0.00391838 * rh.powf(1.5) * (0.023101 * rh).atan()
+            - 4.686035,
+    )
+}
+
+/// Psychrometric (thermodynamic) wet-bulb temperature in °C for a pressure in hPa, solving
+/// the psychrometer equation `e = es(Tw) - A p (T - Tw)` by bisection between the dew point
+/// and the air temperature.
+pub fn wet_bulb_temperature(temperature: f64, dew_point: f64, pressure: f64) -> f64 {
+    let e = vapor_pressure(dew_point);
+    let (mut low, mut high) = (dew_point.min(temperature), temperature);
+    for _ in 0..60 {
+        let tw = (low + high) / 2.0;
+        let a = 6.6e-4 * (1.0 + 0.00115 * tw);
+        if saturation_vapor_pressure(tw) - a * pressure * (temperature - tw) > e {
+            high = tw;
+        } else {
+            low = tw;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// The solar zenith angle in degrees for a UTC time, from the NOAA general solar position
+/// approximation (accurate to a few tenths of a degree).
+pub fn solar_zenith_angle(date: NaiveDateTime, latitude: f64, longitude: f64) -> f64 {
+    let hour = date.hour() as f64 + date.minute() as f64 / 60.0 + date.second() as f64 / 3600.0;
+    let g = 2.0 * PI / 365.0 * (date.ordinal0() as f64 + (hour - 12.0) / 24.0);
+    let equation_of_time = 229.18
+        * (0.000075 + 0.001868 * g.cos()
+            - 0.032077 * g.sin()
+            - 0.014615 * (2.0 * g).cos()
+            - 0.040849 * (2.0 * g).sin());
+    let declination = 0.006918 - 0.399912 * g.cos() + 0.070257 * g.sin()
+        - 0.006758 * (2.0 * g).cos()
+        + 0.000907 * (2.0 * g).sin()
+        - 0.002697 * (3.0 * g).cos()
+        + 0.00148 * (3.0 * g).sin();
+    let true_solar_time = hour * 60.0 + equation_of_time + 4.0 * longitude;
+    let hour_angle = (true_solar_time / 4.0 - 180.0).to_radians();
+    let latitude = latitude.to_radians();
+    let cos_zenith =
+        latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+    cos_zenith.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+const STEFAN_BOLTZMANN: f64 = 5.6696e-8;
+const SOLAR_CONSTANT: f64 = 1367.0;
+const CP: f64 = 1003.5;
+const M_AIR: f64 = 28.97;
+const R_AIR: f64 = 8314.34 / M_AIR;
+const GLOBE_DIAMETER: f64 = 0.0508;
+const GLOBE_EMISSIVITY: f64 = 0.95;
+const GLOBE_ALBEDO: f64 = 0.05;
+const WICK_DIAMETER: f64 = 0.007;
+const WICK_LENGTH: f64 = 0.0254;
+const WICK_EMISSIVITY: f64 = 0.95;
+const WICK_ALBEDO: f64 = 0.4;
+const SURFACE_EMISSIVITY: f64 = 0.999;
+const SURFACE_ALBEDO: f64 = 0.45;
+const MIN_WIND_SPEED: f64 = 0.13;
+
+/// Dynamic viscosity of air in kg/(m s) at `t` Kelvin.
+fn viscosity(t: f64) -> f64 {
+    let omega = (t / 97.0 - 2.9) / 0.4 * -0.034 + 1.048;
+    2.6693e-6 * (M_AIR * t).sqrt() / (3.617 * 3.617 * omega)
+}
+
+/// Thermal conductivity of air in W/(m K) at `t` Kelvin.
+fn thermal_conductivity(t: f64) -> f64 {
+    (CP + 1.25 * R_AIR) * viscosity(t)
+}
+
+/// Diffusivity of water vapor in air in m²/s at `t` Kelvin and `p` hPa.
+fn diffusivity(t: f64, p: f64) -> f64 {
+    let pcrit13 = (36.4 * 218.0f64).powf(1.0 / 3.0);
+    let tcrit512 = (132.0 * 647.3f64).powf(5.0 / 12.0);
+    let tcrit12 = (132.0 * 647.3f64).sqrt();
+    let mmix = (1.0 / M_AIR + 1.0 / 18.015).sqrt();
+    3.64e-4 * (t / tcrit12).powf(2.334) * pcrit13 * tcrit512 * mmix / (p / 1013.25) * 1e-4
+}
+
+/// Latent heat of evaporation in J/kg at `t` Kelvin.
+fn latent_heat(t: f64) -> f64 {
+    (313.15 - t) / 30.0 * -71100.0 + 2.4073e6
+}
+
+fn prandtl(t: f64) -> f64 {
+    CP * viscosity(t) / thermal_conductivity(t)
+}
+
+fn reynolds(diameter: f64, t: f64, p: f64, speed: f64) -> f64 {
+    let density = p * 100.0 / (R_AIR * t);
+    speed * density * diameter / viscosity(t)
+}
+
+/// Convective heat transfer coefficient of a sphere in W/(m² K).
+fn h_sphere(diameter: f64, t: f64, p: f64, speed: f64) -> f64 {
+    let re = reynolds(diameter, t, p, speed);
+    let nu = 2.0 + 0.6 * re.sqrt() * prandtl(t).powf(1.0 / 3.0);
+    nu * thermal_conductivity(t) / diameter
+}
+
+/// Convective heat transfer coefficient of a long cylinder in cross flow in W/(m² K).
+fn h_cylinder(diameter: f64, t: f64, p: f64, speed: f64) -> f64 {
+    let re = reynolds(diameter, t, p, speed);
+    let nu = 0.281 * re.powf(0.6) * prandtl(t).powf(0.44);
+    nu * thermal_conductivity(t) / diameter
+}
+
+/// Flags used for heat stress in US military and occupational guidance, by outdoor WBGT.
+/// The limits are whole degrees Fahrenheit, each flag starting at its lower limit.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HeatStressCategory {
+    /// Below 78 °F (25.6 °C).
+    None,
+    /// From 78 °F (25.6 °C) to below 82 °F (27.8 °C).
+    White,
+    /// From 82 °F (27.8 °C) to below 85 °F (29.4 °C).
+    Green,
+    /// From 85 °F (29.4 °C) to below 88 °F (31.1 °C).
+    Yellow,
+    /// From 88 °F (31.1 °C) to below 90 °F (32.2 °C).
+    Red,
+    /// 90 °F (32.2 °C) and above.
+    Black,
+}
+
+impl HeatStressCategory {
+    pub fn from_wbgt(wbgt: f64) -> HeatStressCategory {
+        let f = wbgt * 9.0 / 5.0 + 32.0;
+        match f {
+            f if f >= 90.0 => HeatStressCategory::Black,
+            f if f >= 88.0 => HeatStressCategory::Red,
+            f if f >= 85.0 => HeatStressCategory::Yellow,
+            f if f >= 82.0 => HeatStressCategory::Green,
+            f if f >= 78.0 => HeatStressCategory::White,
+            _ => HeatStressCategory::None,
+        }
+    }
+}
+
+/// An outdoor Wet Bulb Globe Temperature estimate, following the energy balance model of
+/// Liljegren et al. (2008) for the black globe and the natural wet-bulb wick.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Wbgt {
+    /// Psychrometric wet-bulb temperature in °C.
+    pub wet_bulb: f64,
+    /// Natural (unaspirated, sun exposed) wet-bulb temperature in °C.
+    pub natural_wet_bulb: f64,
+    /// Black globe temperature in °C.
+    pub globe_temperature: f64,
+    /// `0.7 Tnwb + 0.2 Tg + 0.1 Ta` in °C.
+    pub wbgt: f64,
+    pub category: HeatStressCategory,
+    /// The global horizontal irradiance used in W/m², `None` when the record had none
+    /// and the estimate is for shade.
+    pub solar_irradiance: Option<f64>,
+}
+
+impl Wbgt {
+    /// Estimates WBGT from air temperature and dew point in °C, station pressure in hPa,
+    /// wind speed in m/s at 10 m, global horizontal irradiance in W/m² and the solar
+    /// zenith angle in degrees.
+    pub fn estimate(
+        temperature: f64,
+        dew_point: f64,
+        pressure: f64,
+        wind_speed: f64,
+        solar_irradiance: f64,
+        zenith: f64,
+    ) -> Wbgt {
+        let t_air = temperature + 273.15;
+        let rh = relative_humidity(temperature, dew_point) / 100.0;
+        let e_air = vapor_pressure(dew_point);
+        // reduce the 10 m wind to the 2 m globe height with a neutral power law profile
+        let speed = (wind_speed * (2.0f64 / 10.0).powf(0.15)).max(MIN_WIND_SPEED);
+
+        let cos_zenith = zenith.to_radians().cos();
+        let (solar, direct_fraction) = if zenith < 89.5 && solar_irradiance > 0.0 {
+            let top_of_atmosphere = SOLAR_CONSTANT * cos_zenith;
+            let solar = solar_irradiance.min(top_of_atmosphere);
+            let clearness = (solar / top_of_atmosphere).min(0.85);
+            let direct = (3.0 - 1.34 * clearness - 1.65 / clearness).exp();
+            (solar, direct.clamp(0.0, 0.9))
+        } else {
+            (0.0, 0.0)
+        };
+
+        let emissivity_air = 0.575 * (rh * saturation_vapor_pressure(temperature)).powf(1.0 / 7.0);
+        let longwave = 0.5 * (emissivity_air * t_air.powi(4) + SURFACE_EMISSIVITY * t_air.powi(4));
+
+        let mut t_globe = t_air;
+        for _ in 0..100 {
+            let t_ref = 0.5 * (t_globe + t_air);
+            let h = h_sphere(GLOBE_DIAMETER, t_ref, pressure, speed);
+            let next = (longwave - h / (STEFAN_BOLTZMANN * GLOBE_EMISSIVITY) * (t_globe - t_air)
+                + solar / (2.0 * STEFAN_BOLTZMANN * GLOBE_EMISSIVITY)
+                    * (1.0 - GLOBE_ALBEDO)
+                    * (direct_fraction * (1.0 / (2.0 * cos_zenith.max(0.01)) - 1.0)
+                        + 1.0
+                        + SURFACE_ALBEDO))
+                .max(0.0)
+                .powf(0.25);
+            if (next - t_globe).abs() < 0.02 {
+                t_globe = next;
+                break;
+            }
+            t_globe = 0.9 * t_globe + 0.1 * next;
+        }
+
+        let mut t_wick = dew_point + 273.15;
+        let tan_zenith = zenith.to_radians().tan().clamp(0.0, 100.0);
+        for _ in 0..100 {
+            let t_ref = 0.5 * (t_wick + t_air);
+            let h = h_cylinder(WICK_DIAMETER, t_ref, pressure, speed);
+            let radiation = STEFAN_BOLTZMANN * WICK_EMISSIVITY * (longwave - t_wick.powi(4))
+                + (1.0 - WICK_ALBEDO)
+                    * solar
+                    * ((1.0 - direct_fraction) * (1.0 + 0.25 * WICK_DIAMETER / WICK_LENGTH)
+                        + direct_fraction * (tan_zenith / PI + 0.25 * WICK_DIAMETER / WICK_LENGTH)
+                        + SURFACE_ALBEDO);
+            let e_wick = saturation_vapor_pressure(t_wick - 273.15);
+            let density = pressure * 100.0 / (R_AIR * t_ref);
+            let schmidt = viscosity(t_ref) / (density * diffusivity(t_ref, pressure));
+            let next = t_air
+                - latent_heat(t_ref) * EPSILON / CP * (e_wick - e_air) / (pressure - e_wick)
+                    * (prandtl(t_ref) / schmidt).powf(0.56)
+                + radiation / h;
+            if (next - t_wick).abs() < 0.02 {
+                t_wick = next;
+                break;
+            }
+            t_wick = 0.9 * t_wick + 0.1 * next;
+        }
+
+        let natural_wet_bulb = t_wick - 273.15;
+        let globe_temperature = t_globe - 273.15;
+        let wbgt = 0.7 * natural_wet_bulb + 0.2 * globe_temperature + 0.1 * temperature;
+        Wbgt {
+            wet_bulb: wet_bulb_temperature(temperature, dew_point, pressure),
+            natural_wet_bulb,
+            globe_temperature,
+            wbgt,
+            category: HeatStressCategory::from_wbgt(wbgt),
+            solar_irradiance: Some(solar_irradiance),
+        }
+    }
+
+    /// Estimates WBGT for a `Record`, using the `GM1` global irradiance or the `GH1` average
+    /// solar radiation when reported. Without either the estimate is for shade.
+    /// `None` when the temperature, dew point or wind speed is missing.
+    pub fn from_record(record: &Record) -> Option<Wbgt> {
+        let temperature = record.tmp().air_temperature()?;
+        let dew_point = record.dew().dew_point_temperature()?;
+        let wind_speed = record.wnd().speed_rate()?;
+        let pressure = station_pressure(record).unwrap_or_else(|| {
+            sea_level_to_station_pressure(STANDARD_PRESSURE, record.elevation(), None)
+        });
+        let solar = record
+            .gm1()
+            .and_then(|gm1| gm1.global_irradiance())
+            .or_else(|| record.gh1().and_then(|gh1| gh1.avg_solar_radiation()));
+        let zenith = match (record.latitude(), record.longitude()) {
+            (Some(lat), Some(lon)) => solar_zenith_angle(record.date(), lat, lon),
+            _ => 90.0,
+        };
+        let mut wbgt = Wbgt::estimate(
+            temperature,
+            dew_point,
+            pressure,
+            wind_speed,
+            solar.unwrap_or(0.0),
+            zenith,
+        );
+        wbgt.solar_irradiance = solar;
+        Some(wbgt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derived::psychrometrics::dew_point;
+
+    #[test]
+    fn test_wet_bulb() {
+        // Stull's own example, 13.7 °C at 20 °C and 50%
+        assert!((stull_wet_bulb(20.0, 50.0).unwrap() - 13.7).abs() < 0.05);
+        assert_eq!(stull_wet_bulb(20.0, 2.0), None);
+        assert_eq!(stull_wet_bulb(-25.0, 50.0), None);
+        // the psychrometric chart gives 13.8 °C for 20 °C and 50%, and 23.0 °C for 30 °C
+        // with a 20 °C dew point at sea level
+        let tw = wet_bulb_temperature(20.0, dew_point(20.0, 50.0), STANDARD_PRESSURE);
+        assert!((tw - 13.8).abs() < 0.2);
+        let tw = wet_bulb_temperature(30.0, 20.0, STANDARD_PRESSURE);
+        assert!((tw - 23.0).abs() < 0.2);
+        // saturated air is at its wet-bulb, and lower pressure evaporates more
+        assert!((wet_bulb_temperature(15.0, 15.0, STANDARD_PRESSURE) - 15.0).abs() < 1e-6);
+        assert!(wet_bulb_temperature(30.0, 20.0, 850.0) < tw);
+    }
+
+    #[test]
+    fn test_solar_zenith_angle() {
+        let date = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").unwrap();
+        // noon on the June solstice at 40° N is 40 - 23.44 = 16.56° from the zenith
+        let zenith = solar_zenith_angle(date("2020-06-21T12:00:00"), 40.0, 0.0);
+        assert!((zenith - 16.56).abs() < 0.3);
+        // and noon on the December solstice in New York, 17 UTC, 40.77 + 23.44 = 64.21°
+        let zenith = solar_zenith_angle(date("2020-12-21T17:00:00"), 40.77, -73.88);
+        assert!((zenith - 64.21).abs() < 0.3);
+        // the sun is overhead at the equator at the equinox, about 8 minutes before 12 UTC
+        assert!(solar_zenith_angle(date("2020-03-20T12:00:00"), 0.0, 0.0) < 2.5);
+        assert!(solar_zenith_angle(date("2020-03-20T00:00:00"), 0.0, 0.0) > 170.0);
+    }
+
+    #[test]
+    fn test_wbgt() {
+        // in saturated shade the globe and the wick stay close to the air temperature
+        let shade = Wbgt::estimate(25.0, 25.0, STANDARD_PRESSURE, 3.0, 0.0, 90.0);
+        assert!((shade.globe_temperature - 25.0).abs() < 0.5);
+        assert!((shade.natural_wet_bulb - 25.0).abs() < 0.5);
+        assert_eq!(shade.wet_bulb, 25.0);
+
+        // on a sunny summer afternoon the globe runs about 15 °C above the air, and the sun
+        // warms the natural wet-bulb above the psychrometric one, for a WBGT near 30 °C
+        let sun = Wbgt::estimate(30.0, 20.0, STANDARD_PRESSURE, 2.0, 800.0, 30.0);
+        assert!(sun.globe_temperature > 43.0 && sun.globe_temperature < 48.0);
+        assert!(sun.natural_wet_bulb > sun.wet_bulb && sun.natural_wet_bulb < 27.0);
+        assert!((sun.wbgt - 29.8).abs() < 0.5);
+        assert_eq!(sun.category, HeatStressCategory::Yellow);
+        // more wind cools the globe
+        let windy = Wbgt::estimate(30.0, 20.0, STANDARD_PRESSURE, 8.0, 800.0, 30.0);
+        assert!(windy.globe_temperature < sun.globe_temperature);
+    }
+
+    #[test]
+    fn test_heat_stress_category() {
+        let celsius = |f: f64| (f - 32.0) * 5.0 / 9.0;
+        for (f, below, at) in [
+            (78.0, HeatStressCategory::None, HeatStressCategory::White),
+            (82.0, HeatStressCategory::White, HeatStressCategory::Green),
+            (85.0, HeatStressCategory::Green, HeatStressCategory::Yellow),
+            (88.0, HeatStressCategory::Yellow, HeatStressCategory::Red),
+            (90.0, HeatStressCategory::Red, HeatStressCategory::Black),
+        ] {
+            assert_eq!(HeatStressCategory::from_wbgt(celsius(f - 0.01)), below);
+            assert_eq!(HeatStressCategory::from_wbgt(celsius(f + 0.01)), at);
+        }
+        // 29.4 °C is 84.9 °F, still green
+        assert_eq!(
+            HeatStressCategory::from_wbgt(29.4),
+            HeatStressCategory::Green
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use crate::fields::mandatory::Wind;
+use crate::model::Record;
+use chrono::{DateTime, Duration, NaiveDateTime};
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+
+/// The direction in degrees the wind blows from for (u, v) components, `None` for a zero vector.
+pub fn direction_from_components(u: f64, v: f64) -> Option<f64> {
+    if u.hypot(v) < 1e-9 {
+        return None;
+    }
+    Some((-u).atan2(-v).to_degrees().rem_euclid(360.0))
+}
+
+/// Wind statistics over a set of observations.
+///
+/// Calm observations count as zero speed in both means. Variable observations have no
+/// direction, so they count towards the scalar mean speed but not the vector mean.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct WindSummary {
+    /// Observations with a wind speed.
+    pub observations: usize,
+    pub calm: usize,
+    pub variable: usize,
+    /// Mean wind speed in m/s, ignoring direction.
+    pub scalar_mean_speed: Option<f64>,
+    /// Mean eastward component in m/s.
+    pub mean_u: Option<f64>,
+    /// Mean northward component in m/s.
+    pub mean_v: Option<f64>,
+    /// Speed of the mean wind vector in m/s.
+    pub vector_mean_speed: Option<f64>,
+    /// Direction of the mean wind vector in degrees, `None` when it has no length.
+    pub vector_mean_direction: Option<f64>,
+    /// Ratio of the vector mean speed to the scalar mean speed of the same observations,
+    /// 1 for a perfectly steady direction and near 0 when directions cancel out.
+    pub persistence: Option<f64>,
+}
+
+impl WindSummary {
+    pub fn from_winds<'a, I>(winds: I) -> WindSummary
+    where
+        I: IntoIterator<Item = &'a Wind>,
+    {
+        let mut summary = WindSummary::default();
+        let mut speed_sum = 0.0;
+        let (mut u_sum, mut v_sum, mut directional_speed_sum, mut directional) = (0.0, 0.0, 0.0, 0);
+
+        for wind in winds {
+            let speed = if wind.is_calm() {
+                0.0
+            } else {
+                match wind.speed_rate() {
+                    Some(speed) => speed,
+                    None => continue,
+                }
+            };
+            summary.observations += 1;
+            speed_sum += speed;
+            if wind.is_calm() {
+                summary.calm += 1;
+            } else if wind.is_variable() {
+                summary.variable += 1;
+            }
+            if let Some((u, v)) = wind.components() {
+                u_sum += u;
+                v_sum += v;
+                directional_speed_sum += speed;
+                directional += 1;
+            }
+        }
+
+        if summary.observations > 0 {
+            summary.scalar_mean_speed = Some(speed_sum / summary.observations as f64);
+        }
+        if directional > 0 {
+            let (u, v) = (u_sum / directional as f64, v_sum / directional as f64);
+            let speed = u.hypot(v);
+            summary.mean_u = Some(u);
+            summary.mean_v = Some(v);
+            summary.vector_mean_speed = Some(speed);
+            summary.vector_mean_direction = direction_from_components(u, v);
+            let scalar = directional_speed_sum / directional as f64;
+            summary.persistence = if scalar > 0.0 {
+                Some(speed / scalar)
+            } else {
+                None
+            };
+        }
+        summary
+    }
+
+    /// Summarizes the records with a date in `start..end`.
+    pub fn between<'a, I>(records: I, start: NaiveDateTime, end: NaiveDateTime) -> WindSummary
+    where
+        I: IntoIterator<Item = &'a Record>,
+    {
+        WindSummary::from_winds(
+            records
+                .into_iter()
+                .filter(|r| r.date() >= start && r.date() < end)
+                .map(|r| r.wnd()),
+        )
+    }
+}
+
+/// A `WindSummary` for the window starting at `start`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct WindWindow {
+    pub start: NaiveDateTime,
+    pub summary: WindSummary,
+}
+
+/// Summarizes records in consecutive windows of length `window`, aligned to midnight
+/// 1970-01-01 so that e.g. 3 hour windows start at 00, 03, 06 UTC. Windows without
+/// records are omitted.
+pub fn summarize_windows<'a, I>(records: I, window: Duration) -> Vec<WindWindow>
+where
+    I: IntoIterator<Item = &'a Record>,
+{
+    let seconds = window.num_seconds().max(1);
+    let mut windows: BTreeMap<i64, Vec<&Wind>> = BTreeMap::new();
+    for record in records {
+        let bucket = record.date().and_utc().timestamp().div_euclid(seconds);
+        windows.entry(bucket).or_default().push(record.wnd());
+    }
+    windows
+        .into_iter()
+        .filter_map(|(bucket, winds)| {
+            Some(WindWindow {
+                start: DateTime::from_timestamp(bucket * seconds, 0)?.naive_utc(),
+                summary: WindSummary::from_winds(winds),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vector_mean() {
+        let winds: Vec<Wind> = ["350,1,N,0050,1", "010,1,N,0050,1", "999,9,C,0000,1"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let summary = WindSummary::from_winds(&winds);
+        assert_eq!(summary.observations, 3);
+        assert_eq!(summary.calm, 1);
+        // a naive average of 350 and 10 would be 180
+        let direction = summary.vector_mean_direction.unwrap();
+        assert!(direction.min(360.0 - direction) < 0.01);
+        assert!((summary.scalar_mean_speed.unwrap() - 10.0 / 3.0).abs() < 1e-9);
+        assert!(summary.vector_mean_speed.unwrap() < summary.scalar_mean_speed.unwrap());
+
+        let winds: Vec<Wind> = ["090,1,N,0050,1", "270,1,N,0050,1", "999,9,V,0030,1"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let summary = WindSummary::from_winds(&winds);
+        assert_eq!(summary.variable, 1);
+        assert!(summary.persistence.unwrap() < 1e-9);
+        assert_eq!(summary.vector_mean_direction, None);
+    }
+}
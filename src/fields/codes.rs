@@ -9,6 +9,9 @@ impl CodeRecord {
         //trim whitespace from value
         CodeRecord (value.trim().to_string()) 
     }
+    pub fn code(&self) -> &str {
+        &self.0
+    }
 }
 
 pub static BOOL_CODES: phf::Map<&'static str, &'static str> = phf_map! {
@@ -33,6 +33,45 @@ pub struct Wind {
     speed_rate: Option<RecordValue<f64>>,
 }
 
+impl Wind {
+    pub fn direction_angle(&self) -> Option<i32> {
+        self.direction_angle.as_ref().map(|v| v.value())
+    }
+    pub fn type_code(&self) -> &str {
+        self.type_code.code()
+    }
+    pub fn speed_rate(&self) -> Option<f64> {
+        self.speed_rate.as_ref().map(|v| v.value())
+    }
+    pub fn is_calm(&self) -> bool {
+        self.type_code.code() == "C" || self.speed_rate() == Some(0.0)
+    }
+    pub fn is_variable(&self) -> bool {
+        self.type_code.code() == "V"
+    }
+    /// The eastward component of the wind in m/s, zero when calm and `None` when variable.
+    pub fn u(&self) -> Option<f64> {
+        self.components().map(|(u, _)| u)
+    }
+    /// The northward component of the wind in m/s, zero when calm and `None` when variable.
+    pub fn v(&self) -> Option<f64> {
+        self.components().map(|(_, v)| v)
+    }
+    /// The (u, v) components in m/s. The direction angle is where the wind blows from,
+    /// so a north wind has a negative v.
+    pub fn components(&self) -> Option<(f64, f64)> {
+        if self.is_calm() {
+            return Some((0.0, 0.0));
+        }
+        if self.is_variable() {
+            return None;
+        }
+        let speed = self.speed_rate()?;
+        let angle = (self.direction_angle()? as f64).to_radians();
+        Some((-speed * angle.sin(), -speed * angle.cos()))
+    }
+}
+
 impl FromStr for Wind {
     type Err = &'static str;
 
@@ -43,7 +82,7 @@ impl FromStr for Wind {
             direction_angle: RecordValue::<i32>::new(&parts[0], "°", 1),
             direction_quality_code: CodeRecord::new(&parts[1], &QUALITY_CODES),
             type_code: CodeRecord::new(&parts[2], &WIND_OBSERVATION_TYPE_CODES),
-            speed_rate: RecordValue::<f64>::new(&parts[3], "m/s", 1000f64),
+            speed_rate: RecordValue::<f64>::new(&parts[3], "m/s", 10f64),
         })
     }
 }
@@ -76,6 +115,19 @@ pub struct Ceiling {
     cavok: CodeRecord,
 }
 
+impl Ceiling {
+    /// Ceiling height in meters, 22000 when unlimited.
+    pub fn height(&self) -> Option<i32> {
+        self.height.as_ref().map(|v| v.value())
+    }
+    pub fn is_unlimited(&self) -> bool {
+        self.height() == Some(22000)
+    }
+    pub fn cavok(&self) -> bool {
+        self.cavok.code() == "Y"
+    }
+}
+
 impl FromStr for Ceiling {
     type Err = &'static str;
 
@@ -107,6 +159,13 @@ pub struct Visibility {
     variability_quality_code: CodeRecord,
 }
 
+impl Visibility {
+    /// Visibility in meters.
+    pub fn distance(&self) -> Option<i32> {
+        self.distance.as_ref().map(|v| v.value())
+    }
+}
+
 impl FromStr for Visibility {
     type Err = &'static str;
 
@@ -125,9 +184,17 @@ impl FromStr for Visibility {
 #[derive(DeserializeFromStr, Serialize, Debug, PartialEq)]
 pub struct Temperature {
     /// The temperature of the air.
-    air_temperature: Option<RecordValue<i32>>,
+    air_temperature: Option<RecordValue<f64>>,
     air_temperature_quality_code: CodeRecord,
 }
+impl Temperature {
+    pub fn air_temperature(&self) -> Option<f64> {
+        self.air_temperature.as_ref().map(|v| v.value())
+    }
+    pub fn air_temperature_quality_code(&self) -> &str {
+        self.air_temperature_quality_code.code()
+    }
+}
 impl FromStr for Temperature {
     type Err = &'static str;
 
@@ -135,7 +202,7 @@ impl FromStr for Temperature {
         let parts = get_parts(s)?;
 
         Ok(Temperature {
-            air_temperature: RecordValue::<i32>::new(&parts[0], "°C", 10),
+            air_temperature: RecordValue::<f64>::new(&parts[0], "°C", 10f64),
             air_temperature_quality_code: CodeRecord::new(&parts[1], &QUALITY_CODES),
         })
     }
@@ -143,9 +210,14 @@ impl FromStr for Temperature {
 #[derive(DeserializeFromStr, Serialize, Debug, PartialEq)]
 pub struct Dew {
     ///The temperature to which a given parcel of air must be cooled at constant pressure and water vapor content in order for saturation to occur.
-    dew_point_temperature: Option<RecordValue<i32>>,
+    dew_point_temperature: Option<RecordValue<f64>>,
     dew_point_temperature_quality_code: CodeRecord,
 }
+impl Dew {
+    pub fn dew_point_temperature(&self) -> Option<f64> {
+        self.dew_point_temperature.as_ref().map(|v| v.value())
+    }
+}
 impl FromStr for Dew {
     type Err = &'static str;
 
@@ -153,7 +225,7 @@ impl FromStr for Dew {
         let parts = get_parts(s)?;
 
         Ok(Dew {
-            dew_point_temperature: RecordValue::<i32>::new(&parts[0], "°C", 10),
+            dew_point_temperature: RecordValue::<f64>::new(&parts[0], "°C", 10f64),
             dew_point_temperature_quality_code: CodeRecord::new(&parts[1], &QUALITY_CODES),
         })
     }
@@ -162,10 +234,19 @@ impl FromStr for Dew {
 /// The air pressure relative to Mean Sea Level (MSL).
 #[derive(DeserializeFromStr, Serialize, Debug, PartialEq)]
 pub struct SeaLevelPressure {
-    pressure: Option<RecordValue<i32>>,
+    pressure: Option<RecordValue<f64>>,
     pressure_quality_code: CodeRecord,
 }
 
+impl SeaLevelPressure {
+    pub fn pressure(&self) -> Option<f64> {
+        self.pressure.as_ref().map(|v| v.value())
+    }
+    pub fn pressure_quality_code(&self) -> &str {
+        self.pressure_quality_code.code()
+    }
+}
+
 impl FromStr for SeaLevelPressure {
     type Err = &'static str;
 
@@ -173,7 +254,7 @@ impl FromStr for SeaLevelPressure {
         let parts = get_parts(s)?;
 
         Ok(SeaLevelPressure {
-            pressure: RecordValue::<i32>::new(&parts[0], "hPa", 10),
+            pressure: RecordValue::<f64>::new(&parts[0], "hPa", 10f64),
             pressure_quality_code: CodeRecord::new(&parts[1], &QUALITY_CODES),
         })
     }
@@ -1,3 +1,4 @@
 pub mod mandatory;
 pub mod optional;
-pub mod codes;
\ No newline at end of file
+pub mod codes;
+pub mod remarks;
\ No newline at end of file
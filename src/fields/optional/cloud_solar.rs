@@ -326,6 +326,18 @@ pub struct GAX {
     cloud_type_quality_code: CodeRecord,
 }
 
+impl GAX {
+    pub fn coverage_code(&self) -> &str {
+        self.coverage_code.code()
+    }
+    pub fn base_height(&self) -> Option<i32> {
+        self.base_height.as_ref().map(|v| v.value())
+    }
+    pub fn cloud_type_code(&self) -> &str {
+        self.cloud_type_code.code()
+    }
+}
+
 impl FromStr for GAX {
     type Err = &'static str;
 
@@ -353,6 +365,16 @@ pub struct GDX {
     characteristic_code: CodeRecord,
 }
 
+impl GDX {
+    /// The summation coverage, `0` clear through `4` overcast, `5` obscured.
+    pub fn coverage_code(&self) -> &str {
+        self.coverage_code.code()
+    }
+    pub fn height_dimension(&self) -> Option<i32> {
+        self.height_dimension.as_ref().map(|v| v.value())
+    }
+}
+
 impl FromStr for GDX {
     type Err = &'static str;
 
@@ -404,6 +426,29 @@ pub struct GF1 {
     high_cloud_genus_code: CodeRecord,
 }
 
+impl GF1 {
+    /// Total cloud cover in oktas, `09` when the sky is obscured.
+    pub fn total_coverage_code(&self) -> &str {
+        self.total_coverage_code.code()
+    }
+    /// Cover of the low clouds, or the middle clouds when there are no low clouds, in oktas.
+    pub fn low_total_coverage_code(&self) -> &str {
+        self.low_total_coverage_code.code()
+    }
+    pub fn low_cloud_genus_code(&self) -> &str {
+        self.low_cloud_genus_code.code()
+    }
+    pub fn low_cloud_base_height(&self) -> Option<i32> {
+        self.low_cloud_base_height.as_ref().map(|v| v.value())
+    }
+    pub fn mid_cloud_genus_code(&self) -> &str {
+        self.mid_cloud_genus_code.code()
+    }
+    pub fn high_cloud_genus_code(&self) -> &str {
+        self.high_cloud_genus_code.code()
+    }
+}
+
 impl FromStr for GF1 {
     type Err = &'static str;
 
@@ -457,35 +502,40 @@ impl FromStr for GGX {
 
 #[derive(DeserializeFromStr, Serialize, Debug, PartialEq)]
 pub struct GH1 {
-    avg_solar_radiation: Option<RecordValue<i32>>,
+    avg_solar_radiation: Option<RecordValue<f64>>,
     avg_solar_radiation_quality_code: CodeRecord,
     avg_solar_radiation_quality_flag: CodeRecord,
-    min_solar_radiation: Option<RecordValue<i32>>,
+    min_solar_radiation: Option<RecordValue<f64>>,
     min_solar_radiation_quality_code: CodeRecord,
     min_solar_radiation_quality_flag: CodeRecord,
-    max_solar_radiation: Option<RecordValue<i32>>,
+    max_solar_radiation: Option<RecordValue<f64>>,
     max_solar_radiation_quality_code: CodeRecord,
     max_solar_radiation_quality_flag: CodeRecord,
-    std_solar_radiation: Option<RecordValue<i32>>,
+    std_solar_radiation: Option<RecordValue<f64>>,
     std_solar_radiation_quality_code: CodeRecord,
     std_solar_radiation_quality_flag: CodeRecord,
 }
+impl GH1 {
+    pub fn avg_solar_radiation(&self) -> Option<f64> {
+        self.avg_solar_radiation.as_ref().map(|v| v.value())
+    }
+}
 impl FromStr for GH1 {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts = get_parts(s)?;
         Ok(GH1 {
-            avg_solar_radiation: RecordValue::new(&parts[0], "W/m^2", 10),
+            avg_solar_radiation: RecordValue::new(&parts[0], "W/m^2", 10f64),
             avg_solar_radiation_quality_code: CodeRecord::new(&parts[1], &QUALITY_CODES),
             avg_solar_radiation_quality_flag: CodeRecord::new(&parts[2], &QUALITY_FLAG_CODES),
-            min_solar_radiation: RecordValue::new(&parts[3], "W/m^2", 10),
+            min_solar_radiation: RecordValue::new(&parts[3], "W/m^2", 10f64),
             min_solar_radiation_quality_code: CodeRecord::new(&parts[4], &QUALITY_CODES),
             min_solar_radiation_quality_flag: CodeRecord::new(&parts[5], &QUALITY_FLAG_CODES),
-            max_solar_radiation: RecordValue::new(&parts[6], "W/m^2", 10),
+            max_solar_radiation: RecordValue::new(&parts[6], "W/m^2", 10f64),
             max_solar_radiation_quality_code: CodeRecord::new(&parts[7], &QUALITY_CODES),
             max_solar_radiation_quality_flag: CodeRecord::new(&parts[8], &QUALITY_FLAG_CODES),
-            std_solar_radiation: RecordValue::new(&parts[9], "W/m^2", 10),
+            std_solar_radiation: RecordValue::new(&parts[9], "W/m^2", 10f64),
             std_solar_radiation_quality_code: CodeRecord::new(&parts[10], &QUALITY_CODES),
             std_solar_radiation_quality_flag: CodeRecord::new(&parts[11], &QUALITY_FLAG_CODES),
         })
@@ -557,6 +607,18 @@ pub struct GM1 {
     uvb_global_irradiance_quality_code: CodeRecord,
 }
 
+impl GM1 {
+    pub fn global_irradiance(&self) -> Option<f64> {
+        self.global_irradiance.as_ref().map(|v| v.value() as f64)
+    }
+    pub fn direct_beam_irradiance(&self) -> Option<f64> {
+        self.direct_beam_irradiance.as_ref().map(|v| v.value() as f64)
+    }
+    pub fn diffuse_irradiance(&self) -> Option<f64> {
+        self.diffuse_irradiance.as_ref().map(|v| v.value() as f64)
+    }
+}
+
 impl FromStr for GM1 {
     type Err = &'static str;
 
@@ -654,6 +716,18 @@ pub struct GP1 {
     mod_diffuse_horizontal_uncertainty: Option<RecordValue<i32>>,
 }
 
+impl GP1 {
+    pub fn mod_global_horizontal(&self) -> Option<f64> {
+        self.mod_global_horizontal.as_ref().map(|v| v.value() as f64)
+    }
+    pub fn mod_direct_normal(&self) -> Option<f64> {
+        self.mod_direct_normal.as_ref().map(|v| v.value() as f64)
+    }
+    pub fn mod_diffuse_horizontal(&self) -> Option<f64> {
+        self.mod_diffuse_horizontal.as_ref().map(|v| v.value() as f64)
+    }
+}
+
 impl FromStr for GP1 {
     type Err = &'static str;
 
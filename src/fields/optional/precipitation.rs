@@ -59,6 +59,23 @@ pub struct AAX {
     quality_code: CodeRecord,
 }
 
+impl AAX {
+    /// The accumulation period in hours.
+    pub fn period_quantity(&self) -> Option<i8> {
+        self.period_quantity.as_ref().map(|v| v.value())
+    }
+    /// The depth in mm.
+    pub fn depth_dimension(&self) -> Option<f64> {
+        self.depth_dimension.as_ref().map(|v| v.value())
+    }
+    pub fn condition_code(&self) -> &str {
+        self.condition_code.code()
+    }
+    pub fn quality_code(&self) -> &str {
+        self.quality_code.code()
+    }
+}
+
 impl FromStr for AAX {
     type Err = &'static str;
 
@@ -84,6 +101,15 @@ pub struct AB1 {
     quality_code: CodeRecord,
 }
 
+impl AB1 {
+    /// The monthly total in mm.
+    pub fn depth_dimension(&self) -> Option<f64> {
+        self.depth_dimension.as_ref().map(|v| v.value())
+    }
+    pub fn condition_code(&self) -> &str {
+        self.condition_code.code()
+    }
+}
 impl FromStr for AB1 {
     type Err = &'static str;
 
@@ -172,6 +198,24 @@ pub struct AE1 {
     days_100_quality: CodeRecord,
 }
 
+impl AE1 {
+    /// Days in the month with at least 0.01 inch of precipitation.
+    pub fn days_01(&self) -> Option<i8> {
+        self.days_01.as_ref().map(|v| v.value())
+    }
+    /// Days with at least 0.10 inch.
+    pub fn days_10(&self) -> Option<i8> {
+        self.days_10.as_ref().map(|v| v.value())
+    }
+    /// Days with at least 0.50 inch.
+    pub fn days_50(&self) -> Option<i8> {
+        self.days_50.as_ref().map(|v| v.value())
+    }
+    /// Days with at least 1.00 inch.
+    pub fn days_100(&self) -> Option<i8> {
+        self.days_100.as_ref().map(|v| v.value())
+    }
+}
 impl FromStr for AE1 {
     type Err = &'static str;
 
@@ -268,6 +312,22 @@ pub struct AJ1 {
     equivalent_water_condition_quality_code: CodeRecord,
 }
 
+impl AJ1 {
+    /// Snow depth in cm.
+    pub fn depth_dimension(&self) -> Option<i16> {
+        self.depth_dimension.as_ref().map(|v| v.value())
+    }
+    pub fn condition_code(&self) -> &str {
+        self.condition_code.code()
+    }
+    /// Water equivalent of the snow in mm.
+    pub fn equivalent_water_depth_dimension(&self) -> Option<f64> {
+        self.equivalent_water_depth_dimension
+            .as_ref()
+            .map(|v| v.value())
+    }
+}
+
 impl FromStr for AJ1 {
     type Err = &'static str;
 
@@ -288,10 +348,21 @@ impl FromStr for AJ1 {
 pub struct AK1 {
     depth_dimension: Option<RecordValue<i16>>,
     condition_code: CodeRecord,
-    dates_of_occourence: [String; 3],
+    dates_of_occourence: String,
     quality_code: CodeRecord,
 }
 
+impl AK1 {
+    /// Greatest snow depth of the month in cm.
+    pub fn depth_dimension(&self) -> Option<i16> {
+        self.depth_dimension.as_ref().map(|v| v.value())
+    }
+    /// The days of the month it occurred on, as up to three two digit days.
+    pub fn dates_of_occourence(&self) -> &str {
+        &self.dates_of_occourence
+    }
+}
+
 impl FromStr for AK1 {
     type Err = &'static str;
 
@@ -301,8 +372,8 @@ impl FromStr for AK1 {
         Ok(AK1 {
             depth_dimension: RecordValue::new(&parts[0], "cm", 1),
             condition_code: CodeRecord::new(&parts[1], &CONDITION_CODES),
-            dates_of_occourence: [parts[2].clone(), parts[3].clone(), parts[4].clone()],
-            quality_code: CodeRecord::new(&parts[5], &QUALITY_CODES),
+            dates_of_occourence: parts[2].clone(),
+            quality_code: CodeRecord::new(&parts[3], &QUALITY_CODES),
         })
     }
 }
@@ -314,6 +385,19 @@ pub struct ALX {
     condition_code: CodeRecord,
     quality_code: CodeRecord,
 }
+impl ALX {
+    /// Accumulation period in hours.
+    pub fn period_quantity(&self) -> Option<i8> {
+        self.period_quantity.as_ref().map(|v| v.value())
+    }
+    /// Snow accumulation in cm.
+    pub fn depth_dimension(&self) -> Option<i16> {
+        self.depth_dimension.as_ref().map(|v| v.value())
+    }
+    pub fn condition_code(&self) -> &str {
+        self.condition_code.code()
+    }
+}
 impl FromStr for ALX {
     type Err = &'static str;
 
@@ -336,6 +420,16 @@ pub struct AM1 {
     dates_of_occourence: [String; 3],
     quality_code: CodeRecord,
 }
+impl AM1 {
+    /// Greatest snowfall in 24 hours of the month in cm.
+    pub fn depth_dimension(&self) -> Option<f64> {
+        self.depth_dimension.as_ref().map(|v| v.value())
+    }
+    /// The days it occurred on, each as the two digit days the 24 hours began and ended.
+    pub fn dates_of_occourence(&self) -> &[String; 3] {
+        &self.dates_of_occourence
+    }
+}
 impl FromStr for AM1 {
     type Err = &'static str;
 
@@ -351,18 +445,31 @@ impl FromStr for AM1 {
                 parts[3].to_string(),
                 parts[4].to_string(),
             ],
-            quality_code: CodeRecord::new(&parts[4], &QUALITY_CODES),
+            quality_code: CodeRecord::new(&parts[5], &QUALITY_CODES),
         })
     }
 }
 
 #[derive(DeserializeFromStr, Serialize, Debug, PartialEq)]
 pub struct AN1 {
-    period_quantity: Option<RecordValue<i8>>,
+    period_quantity: Option<RecordValue<i16>>,
     depth_dimension: Option<RecordValue<f64>>,
     condition_code: CodeRecord,
     quality_code: CodeRecord,
 }
+impl AN1 {
+    /// Accumulation period in hours, up to 744.
+    pub fn period_quantity(&self) -> Option<i16> {
+        self.period_quantity.as_ref().map(|v| v.value())
+    }
+    /// Snow accumulation in cm.
+    pub fn depth_dimension(&self) -> Option<f64> {
+        self.depth_dimension.as_ref().map(|v| v.value())
+    }
+    pub fn condition_code(&self) -> &str {
+        self.condition_code.code()
+    }
+}
 impl FromStr for AN1 {
     type Err = &'static str;
 
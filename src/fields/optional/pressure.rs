@@ -33,6 +33,14 @@ pub struct MA1 {
     station_pressure_rate: Option<RecordValue<f64>>,
     station_pressure_quality_code: CodeRecord,
 }
+impl MA1 {
+    pub fn altimeter_setting(&self) -> Option<f64> {
+        self.altimeter_setting_rate.as_ref().map(|v| v.value())
+    }
+    pub fn station_pressure(&self) -> Option<f64> {
+        self.station_pressure_rate.as_ref().map(|v| v.value())
+    }
+}
 impl FromStr for MA1 {
     type Err = &'static str;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -55,6 +63,20 @@ pub struct MD1 {
     twenty_four_hour_quantity: Option<RecordValue<f64>>,
     twenty_four_hour_quantity_quality_code: CodeRecord,
 }
+impl MD1 {
+    /// The WMO pressure tendency characteristic, `0`-`3` rising, `4` steady, `5`-`8` falling.
+    pub fn tendency_code(&self) -> &str {
+        self.tendency_code.code()
+    }
+    /// The absolute pressure change over three hours in hPa.
+    pub fn three_hour_quantity(&self) -> Option<f64> {
+        self.three_hour_quantity.as_ref().map(|v| v.value())
+    }
+    /// The absolute pressure change over 24 hours in hPa.
+    pub fn twenty_four_hour_quantity(&self) -> Option<f64> {
+        self.twenty_four_hour_quantity.as_ref().map(|v| v.value())
+    }
+}
 impl FromStr for MD1 {
     type Err = &'static str;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -136,6 +158,14 @@ pub struct MH1 {
     avg_sea_level_pressure_month: Option<RecordValue<f64>>,
     avg_sea_level_pressure_month_quality_code: CodeRecord,
 }
+impl MH1 {
+    pub fn avg_station_pressure_month(&self) -> Option<f64> {
+        self.avg_station_pressure_month.as_ref().map(|v| v.value())
+    }
+    pub fn avg_sea_level_pressure_month(&self) -> Option<f64> {
+        self.avg_sea_level_pressure_month.as_ref().map(|v| v.value())
+    }
+}
 impl FromStr for MH1 {
     type Err = &'static str;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -157,6 +187,29 @@ pub struct MK1 {
     min_sea_level_pressure_month_date_time: Option<RecordValue<isize>>,
     min_sea_level_pressure_month_quality_code: CodeRecord,
 }
+impl MK1 {
+    pub fn max_sea_level_pressure_month(&self) -> Option<f64> {
+        self.max_sea_level_pressure_month.as_ref().map(|v| v.value())
+    }
+    /// The day and time of the maximum as `DDHHMM`.
+    pub fn max_sea_level_pressure_month_date_time(&self) -> Option<isize> {
+        self.max_sea_level_pressure_month_date_time
+            .as_ref()
+            .map(|v| v.value())
+    }
+    pub fn min_sea_level_pressure_month(&self) -> Option<f64> {
+        self.min_sea_level_pressure_month.as_ref().map(|v| v.value())
+    }
+    /// The day and time of the minimum as `DDHHMM`.
+    pub fn min_sea_level_pressure_month_date_time(&self) -> Option<isize> {
+        self.min_sea_level_pressure_month_date_time
+            .as_ref()
+            .map(|v| v.value())
+    }
+    pub fn min_sea_level_pressure_month_quality_code(&self) -> &str {
+        self.min_sea_level_pressure_month_quality_code.code()
+    }
+}
 impl FromStr for MK1 {
     type Err = &'static str;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
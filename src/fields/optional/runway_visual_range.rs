@@ -21,6 +21,16 @@ pub struct ED1 {
     visibility_dimension: Option<RecordValue<f64>>,
     quality_code: CodeRecord,
 }
+impl ED1 {
+    /// The runway direction in degrees, from the runway number.
+    pub fn direction_angle(&self) -> Option<f64> {
+        self.direction_angle.as_ref().map(|v| v.value())
+    }
+    /// `L`, `C` or `R` for parallel runways, `U` when unknown.
+    pub fn designator_code(&self) -> &str {
+        self.designator_code.code()
+    }
+}
 impl FromStr for ED1 {
     type Err = &'static str;
 
@@ -39,6 +39,22 @@ pub struct KAX {
     air_temperature: Option<RecordValue<f64>>,
     air_temperature_quality_code: CodeRecord,
 }
+impl KAX {
+    /// The period the extreme was taken over, in hours.
+    pub fn period_quantity(&self) -> Option<f64> {
+        self.period_quantity.as_ref().map(|v| v.value())
+    }
+    /// `M` or `P` for an (estimated) maximum, `N` or `O` for an (estimated) minimum.
+    pub fn code(&self) -> &str {
+        self.code.code()
+    }
+    pub fn air_temperature(&self) -> Option<f64> {
+        self.air_temperature.as_ref().map(|v| v.value())
+    }
+    pub fn air_temperature_quality_code(&self) -> &str {
+        self.air_temperature_quality_code.code()
+    }
+}
 impl FromStr for KAX {
     type Err = &'static str;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -107,6 +123,24 @@ pub struct KE1 {
     min_temp_32_f_days_quality_code: CodeRecord,
     min_temp_0_f_days: Option<RecordValue<isize>>,
 }
+impl KE1 {
+    /// Days in the month with a maximum temperature of 32 °F or below.
+    pub fn max_temp_32_f_days(&self) -> Option<isize> {
+        self.max_temp_32_f_days.as_ref().map(|v| v.value())
+    }
+    /// Days with a maximum of 90 °F or above.
+    pub fn max_temp_90_f_days(&self) -> Option<isize> {
+        self.max_temp_90_f_days.as_ref().map(|v| v.value())
+    }
+    /// Days with a minimum of 32 °F or below.
+    pub fn min_temp_32_f_days(&self) -> Option<isize> {
+        self.min_temp_32_f_days.as_ref().map(|v| v.value())
+    }
+    /// Days with a minimum of 0 °F or below.
+    pub fn min_temp_0_f_days(&self) -> Option<isize> {
+        self.min_temp_0_f_days.as_ref().map(|v| v.value())
+    }
+}
 impl FromStr for KE1 {
     type Err = &'static str;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -372,6 +372,27 @@ pub struct AUX {
     quality_code: CodeRecord,
 }
 
+impl AUX {
+    pub fn intensity_code(&self) -> &str {
+        self.intensity_code.code()
+    }
+    pub fn descriptor_code(&self) -> &str {
+        self.descriptor_code.code()
+    }
+    pub fn precipitation_code(&self) -> &str {
+        self.precipitation_code.code()
+    }
+    pub fn obscuration_code(&self) -> &str {
+        self.obscuration_code.code()
+    }
+    pub fn weather_phenomena_code(&self) -> &str {
+        self.weather_phenomena_code.code()
+    }
+    pub fn combination_indicator_code(&self) -> &str {
+        self.combination_indicator_code.code()
+    }
+}
+
 impl FromStr for AUX {
     type Err = &'static str;
 
@@ -396,6 +417,13 @@ pub struct AWX {
     atmospheric_condition_code: CodeRecord,
 }
 
+impl AWX {
+    /// The two digit WMO automated present weather code.
+    pub fn atmospheric_condition_code(&self) -> &str {
+        self.atmospheric_condition_code.code()
+    }
+}
+
 impl FromStr for AWX {
     type Err = &'static str;
 
@@ -495,6 +523,12 @@ pub struct MWX {
     manual_atmospheric_condition_code: CodeRecord,
     manual_atmospheric_condition_quality_code: CodeRecord,
 }
+impl MWX {
+    /// The two digit WMO present weather (ww) code.
+    pub fn atmospheric_condition_code(&self) -> &str {
+        self.manual_atmospheric_condition_code.code()
+    }
+}
 impl FromStr for MWX {
     type Err = &'static str;
 
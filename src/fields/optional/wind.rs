@@ -38,6 +38,20 @@ pub struct OAX {
     speed_rate: Option<RecordValue<f64>>,
     speed_quality_code: CodeRecord,
 }
+impl OAX {
+    pub fn type_code(&self) -> &str {
+        self.type_code.code()
+    }
+    pub fn period_quantity(&self) -> Option<f64> {
+        self.period_quantity.as_ref().map(|v| v.value())
+    }
+    pub fn speed_rate(&self) -> Option<f64> {
+        self.speed_rate.as_ref().map(|v| v.value())
+    }
+    pub fn speed_quality_code(&self) -> &str {
+        self.speed_quality_code.code()
+    }
+}
 impl FromStr for OAX {
     type Err = &'static str;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -94,6 +108,14 @@ pub struct OC1 {
     speed_rate: Option<RecordValue<f64>>,
     speed_quality_code: CodeRecord,
 }
+impl OC1 {
+    pub fn speed_rate(&self) -> Option<f64> {
+        self.speed_rate.as_ref().map(|v| v.value())
+    }
+    pub fn speed_quality_code(&self) -> &str {
+        self.speed_quality_code.code()
+    }
+}
 impl FromStr for OC1 {
     type Err = &'static str;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -139,6 +161,18 @@ pub struct OEX {
     time: Option<RecordValue<f64>>,
     quality_code: CodeRecord,
 }
+impl OEX {
+    /// `1` for the peak wind speed of the day, see `SUMMARY_TYPE_CODES`.
+    pub fn type_code(&self) -> &str {
+        self.type_code.code()
+    }
+    pub fn speed_rate(&self) -> Option<f64> {
+        self.speed_rate.as_ref().map(|v| v.value())
+    }
+    pub fn quality_code(&self) -> &str {
+        self.quality_code.code()
+    }
+}
 impl FromStr for OEX {
     type Err = &'static str;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
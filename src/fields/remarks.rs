@@ -0,0 +1,129 @@
+use serde::Serialize;
+use serde_with::DeserializeFromStr;
+use std::str::FromStr;
+
+/// Remark identifiers used by ISD.
+pub static REMARK_IDENTIFIERS: [&str; 6] = ["AWY", "HPD", "MET", "SOD", "SOM", "SYN"];
+
+/// One remark, e.g. `MET` with the original METAR text.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Remark {
+    identifier: String,
+    text: String,
+}
+
+impl Remark {
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// The REM section, remarks each written as a three character identifier, a three digit
+/// length and the text.
+///
+/// The lengths in the CSV files don't always match the text, so a remark also ends where
+/// a known identifier starts, and text that has no header is added to the previous remark.
+#[derive(DeserializeFromStr, Serialize, Debug, Clone, PartialEq, Default)]
+pub struct Remarks(Vec<Remark>);
+
+impl Remarks {
+    /// The text of the first remark with the given identifier.
+    pub fn get(&self, identifier: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|r| r.identifier == identifier)
+            .map(|r| r.text.as_str())
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &Remark> {
+        self.0.iter()
+    }
+}
+
+/// The identifier of a remark header at the start of `s`.
+fn header(s: &str) -> Option<&str> {
+    let bytes = s.as_bytes();
+    if bytes.len() >= 6
+        && bytes[..3].iter().all(|b| b.is_ascii_uppercase())
+        && bytes[3..6].iter().all(|b| b.is_ascii_digit())
+    {
+        Some(&s[..3])
+    } else {
+        None
+    }
+}
+
+/// The position of the next known remark header after the start of `s`.
+fn next_known_header(s: &str) -> Option<usize> {
+    s.char_indices()
+        .skip(1)
+        .filter(|(i, _)| s[..*i].ends_with(char::is_whitespace))
+        .map(|(i, _)| i)
+        .find(|i| header(&s[*i..]).is_some_and(|id| REMARK_IDENTIFIERS.contains(&id)))
+}
+
+impl FromStr for Remarks {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut remarks: Vec<Remark> = Vec::new();
+        let mut rest = s.trim();
+        while !rest.is_empty() {
+            let identifier = header(rest);
+            let body = if identifier.is_some() {
+                &rest[6..]
+            } else {
+                rest
+            };
+            let declared = if identifier.is_some() {
+                rest[3..6].parse::<usize>().unwrap_or(0)
+            } else {
+                body.len()
+            };
+            let mut end = body
+                .char_indices()
+                .nth(declared)
+                .map_or(body.len(), |(i, _)| i);
+            if let Some(next) = next_known_header(body) {
+                end = end.min(next);
+            }
+            let text = body[..end].trim().to_string();
+            match (identifier, remarks.last_mut()) {
+                (Some(identifier), _) => remarks.push(Remark {
+                    identifier: identifier.to_string(),
+                    text,
+                }),
+                (None, Some(last)) => {
+                    last.text.push(' ');
+                    last.text.push_str(&text);
+                }
+                (None, None) => remarks.push(Remark {
+                    identifier: String::new(),
+                    text,
+                }),
+            }
+            rest = body[end..].trim_start();
+        }
+        Ok(Remarks(remarks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Remarks;
+
+    #[test]
+    fn test_remarks() {
+        let remarks: Remarks = "SYN05872503 32966 70000 10050 MET10612/31/19 19:51:02 METAR KLGA 010051Z 27009KT 10SM FEW250 06/M04 A2982 RMK AO2 SLP100 T00611044 $ (KLGA)"
+            .parse()
+            .unwrap();
+        assert_eq!(remarks.get("SYN"), Some("72503 32966 70000 10050"));
+        assert_eq!(
+            remarks.get("MET"),
+            Some("12/31/19 19:51:02 METAR KLGA 010051Z 27009KT 10SM FEW250 06/M04 A2982 RMK AO2 SLP100 T00611044 $ (KLGA)")
+        );
+        assert_eq!(remarks.iter().count(), 2);
+    }
+}
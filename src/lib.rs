@@ -0,0 +1,10 @@
+extern crate phf;
+pub mod derived;
+pub mod fields;
+pub mod model;
+pub mod report;
+pub mod series;
+pub mod station;
+pub mod summary;
+pub mod units;
+pub mod util;
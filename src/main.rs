@@ -1,12 +1,7 @@
-extern crate phf;
-mod model;
-use model::Record;
+use noaa_global_hourly::model::Record;
 use std::error::Error;
 use std::io;
 use std::time::Instant;
-mod util;
-mod fields;
-mod units;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let mut rdr = csv::Reader::from_reader(io::stdin());
@@ -1,5 +1,5 @@
 use crate::fields::mandatory::{
-    Ceiling,  SeaLevelPressure, Temperature, Visibility, Wind,
+    Ceiling, Dew, SeaLevelPressure, Temperature, Visibility, Wind,
 };
 use crate::fields::optional::climate_reference_network::*;
 use crate::fields::optional::cloud_solar::*;
@@ -13,6 +13,8 @@ use crate::fields::optional::sea_surface_temperature::*;
 use crate::fields::optional::temperature::*;
 use crate::fields::optional::weather_occurrence::*;
 use crate::fields::optional::wind::*;
+use crate::fields::remarks::Remarks;
+use crate::station::id::StationId;
 use crate::util::*;
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
@@ -24,24 +26,27 @@ use std::str::FromStr;
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(rename_all(serialize = "lowercase", deserialize = "UPPERCASE"))]
 pub struct Record {
-    station: Value<String>,
+    station: Value<StationId>,
     #[serde(serialize_with = "str_from_native_date_time")]
     #[serde(deserialize_with = "naive_date_time_from_str")]
     date: NaiveDateTime,
-    source: Value<String>,
+    #[serde(deserialize_with = "remove_whitespace")]
+    source: String,
     /// The latitude coordinate of a GEOPHYSICAL-POINT-OBSERVATION where Southern Hemisphere is negative.
     latitude: Value<f64>,
     /// The longitude coordinate of a GEOPHYSICAL-POINT-OBSERVATION where values west from 000000 to 179999 are signed negative.
     longitude: Value<f64>,
     elevation: f64,
     name: String,
-    report_type: Value<String>,
+    #[serde(deserialize_with = "remove_whitespace")]
+    report_type: String,
     call_sign: Value<String>,
     quality_control: String,
     wnd: Wind,
     cig: Ceiling,
     vis: Visibility,
     tmp: Temperature,
+    dew: Dew,
     slp: SeaLevelPressure,
     // Precipitation
     aa1: Option<AAX>,
@@ -239,6 +244,183 @@ pub struct Record {
     wd1: Option<WD1>,
     wg1: Option<WG1>,
     wj1: Option<WJ1>,
+    rem: Option<Remarks>,
+}
+
+impl Record {
+    pub fn station(&self) -> Option<&StationId> {
+        self.station.get()
+    }
+    pub fn date(&self) -> NaiveDateTime {
+        self.date
+    }
+    pub fn latitude(&self) -> Option<f64> {
+        self.latitude.get().copied()
+    }
+    pub fn longitude(&self) -> Option<f64> {
+        self.longitude.get().copied()
+    }
+    pub fn elevation(&self) -> f64 {
+        self.elevation
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// `None` when missing. Report types such as `SOD` have no digits, so they can't be
+    /// checked with `is_null`.
+    pub fn report_type(&self) -> Option<&str> {
+        non_empty(&self.report_type)
+    }
+    pub fn source(&self) -> Option<&str> {
+        non_empty(&self.source)
+    }
+    pub fn wnd(&self) -> &Wind {
+        &self.wnd
+    }
+    pub fn cig(&self) -> &Ceiling {
+        &self.cig
+    }
+    pub fn vis(&self) -> &Visibility {
+        &self.vis
+    }
+    pub fn tmp(&self) -> &Temperature {
+        &self.tmp
+    }
+    pub fn dew(&self) -> &Dew {
+        &self.dew
+    }
+    pub fn slp(&self) -> &SeaLevelPressure {
+        &self.slp
+    }
+    /// The liquid precipitation groups AA1-AA4.
+    pub fn aax(&self) -> impl Iterator<Item = &AAX> {
+        [&self.aa1, &self.aa2, &self.aa3, &self.aa4]
+            .into_iter()
+            .flatten()
+    }
+    pub fn ab1(&self) -> Option<&AB1> {
+        self.ab1.as_ref()
+    }
+    pub fn ae1(&self) -> Option<&AE1> {
+        self.ae1.as_ref()
+    }
+    pub fn aj1(&self) -> Option<&AJ1> {
+        self.aj1.as_ref()
+    }
+    pub fn ak1(&self) -> Option<&AK1> {
+        self.ak1.as_ref()
+    }
+    /// The snow accumulation groups AL1-AL4.
+    pub fn alx(&self) -> impl Iterator<Item = &ALX> {
+        [&self.al1, &self.al2, &self.al3, &self.al4]
+            .into_iter()
+            .flatten()
+    }
+    pub fn am1(&self) -> Option<&AM1> {
+        self.am1.as_ref()
+    }
+    pub fn an1(&self) -> Option<&AN1> {
+        self.an1.as_ref()
+    }
+    /// The present weather groups AU1-AU9.
+    pub fn aux(&self) -> impl Iterator<Item = &AUX> {
+        [
+            &self.au1, &self.au2, &self.au3, &self.au4, &self.au5, &self.au6, &self.au7,
+            &self.au8, &self.au9,
+        ]
+        .into_iter()
+        .flatten()
+    }
+    /// The automated present weather groups AW1-AW4.
+    pub fn awx(&self) -> impl Iterator<Item = &AWX> {
+        [&self.aw1, &self.aw2, &self.aw3, &self.aw4]
+            .into_iter()
+            .flatten()
+    }
+    /// The manual present weather groups MW1-MW7.
+    pub fn mwx(&self) -> impl Iterator<Item = &MWX> {
+        [
+            &self.mw1, &self.mw2, &self.mw3, &self.mw4, &self.mw5, &self.mw6, &self.mw7,
+        ]
+        .into_iter()
+        .flatten()
+    }
+    /// The sky cover layer groups GA1-GA6.
+    pub fn gax(&self) -> impl Iterator<Item = &GAX> {
+        [&self.ga1, &self.ga2, &self.ga3, &self.ga4, &self.ga5, &self.ga6]
+            .into_iter()
+            .flatten()
+    }
+    /// The sky cover summation groups GD1-GD6.
+    pub fn gdx(&self) -> impl Iterator<Item = &GDX> {
+        [&self.gd1, &self.gd2, &self.gd3, &self.gd4, &self.gd5, &self.gd6]
+            .into_iter()
+            .flatten()
+    }
+    pub fn remarks(&self) -> Option<&Remarks> {
+        self.rem.as_ref()
+    }
+    /// The text of the remark with the given identifier, e.g. `MET`.
+    pub fn remark(&self, identifier: &str) -> Option<&str> {
+        self.rem.as_ref().and_then(|rem| rem.get(identifier))
+    }
+    pub fn gf1(&self) -> Option<&GF1> {
+        self.gf1.as_ref()
+    }
+    pub fn gh1(&self) -> Option<&GH1> {
+        self.gh1.as_ref()
+    }
+    pub fn gm1(&self) -> Option<&GM1> {
+        self.gm1.as_ref()
+    }
+    pub fn gp1(&self) -> Option<&GP1> {
+        self.gp1.as_ref()
+    }
+    pub fn ma1(&self) -> Option<&MA1> {
+        self.ma1.as_ref()
+    }
+    /// The extreme air temperature groups KA1-KA4.
+    pub fn kax(&self) -> impl Iterator<Item = &KAX> {
+        [&self.ka1, &self.ka2, &self.ka3, &self.ka4]
+            .into_iter()
+            .flatten()
+    }
+    pub fn ke1(&self) -> Option<&KE1> {
+        self.ke1.as_ref()
+    }
+    pub fn mh1(&self) -> Option<&MH1> {
+        self.mh1.as_ref()
+    }
+    pub fn mk1(&self) -> Option<&MK1> {
+        self.mk1.as_ref()
+    }
+    pub fn md1(&self) -> Option<&MD1> {
+        self.md1.as_ref()
+    }
+    pub fn ed1(&self) -> Option<&ED1> {
+        self.ed1.as_ref()
+    }
+    /// The supplementary wind groups OA1-OA5.
+    pub fn oax(&self) -> impl Iterator<Item = &OAX> {
+        [&self.oa1, &self.oa2, &self.oa3, &self.oa4, &self.oa5]
+            .into_iter()
+            .flatten()
+    }
+    /// The summary of day wind groups OE1-OE3.
+    pub fn oex(&self) -> impl Iterator<Item = &OEX> {
+        [&self.oe1, &self.oe2, &self.oe3].into_iter().flatten()
+    }
+    pub fn oc1(&self) -> Option<&OC1> {
+        self.oc1.as_ref()
+    }
+}
+
+fn non_empty(s: &str) -> Option<&str> {
+    if s.is_empty() || s == "99999" {
+        None
+    } else {
+        Some(s)
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
@@ -246,7 +428,15 @@ pub struct RecordValue<T> {
     value: T,
     unit: String,
 }
+impl<T: Copy> RecordValue<T> {
+    pub fn value(&self) -> T {
+        self.value
+    }
+}
 impl<T> RecordValue<T> {
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
     pub fn new(s: &str, unit: &str, divide: T) -> Option<RecordValue<T>>
     where
         T: FromStr + std::ops::Div<Output = T>,
@@ -275,6 +465,12 @@ impl<T> RecordValue<T> {
 #[derive(DeserializeFromStr, Serialize, Debug, PartialEq)]
 pub struct Value<T>(Option<T>);
 
+impl<T> Value<T> {
+    pub fn get(&self) -> Option<&T> {
+        self.0.as_ref()
+    }
+}
+
 impl<T: FromStr> FromStr for Value<T> {
     type Err = T::Err;
     fn from_str(s: &str) -> Result<Self, Self::Err>
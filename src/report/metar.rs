@@ -0,0 +1,937 @@
+use crate::derived::crosswind::gust_speed;
+use crate::fields::mandatory::{Ceiling, Dew, SeaLevelPressure, Temperature, Visibility, Wind};
+use crate::fields::optional::cloud_solar::{GAX, GDX};
+use crate::fields::optional::pressure::MA1;
+use crate::fields::optional::weather_occurrence::AUX;
+use crate::fields::optional::wind::OC1;
+use crate::model::Record;
+use crate::report::{discrepancies, Discrepancy};
+use chrono::{NaiveDateTime, NaiveTime};
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::fmt;
+use std::str::FromStr;
+
+const KNOTS_PER_MS: f64 = 1.943_844;
+const FEET_PER_METER: f64 = 1.0 / 0.3048;
+const METERS_PER_MILE: f64 = 1609.344;
+const HPA_PER_INHG: f64 = 33.8639;
+
+/// The units a METAR is written in.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MetarStyle {
+    /// Visibility in meters, `Q` altimeter in hPa and `CAVOK`.
+    #[default]
+    Icao,
+    /// Visibility in statute miles and `A` altimeter in inches of mercury.
+    UnitedStates,
+}
+
+/// A METAR encoded from a `Record`. Groups that cannot be encoded from the record are
+/// left out.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Metar {
+    /// `METAR`, or `SPECI` for FM-16 reports.
+    pub report: &'static str,
+    pub station: String,
+    pub date: NaiveDateTime,
+    pub wind: Option<String>,
+    pub visibility: Option<String>,
+    pub weather: Vec<String>,
+    pub sky: Vec<String>,
+    pub temperature: Option<String>,
+    pub altimeter: Option<String>,
+}
+
+impl Metar {
+    /// Encodes a record, with `station` as the location indicator, e.g. the ICAO code.
+    pub fn from_record(record: &Record, station: &str, style: MetarStyle) -> Metar {
+        let cavok = style == MetarStyle::Icao && record.cig().cavok();
+        let mut weather: Vec<String> = Vec::new();
+        for aux in record.aux() {
+            match (encode_aux(aux), weather.last_mut()) {
+                (Some(group), Some(last)) if aux.combination_indicator_code() == "3" => {
+                    last.push_str(group.trim_start_matches(['-', '+']))
+                }
+                (Some(group), _) => weather.push(group),
+                (None, _) => {}
+            }
+        }
+        if weather.is_empty() {
+            weather = record
+                .mwx()
+                .filter_map(|mw| present_weather(mw.atmospheric_condition_code()))
+                .map(str::to_string)
+                .collect();
+        }
+
+        Metar {
+            report: match record.report_type() {
+                Some("FM-16") => "SPECI",
+                _ => "METAR",
+            },
+            station: station.to_string(),
+            date: record.date(),
+            wind: encode_wind(record.wnd(), gust_speed(record)),
+            visibility: if cavok {
+                Some("CAVOK".to_string())
+            } else {
+                record.vis().distance().map(|d| encode_visibility(d, style))
+            },
+            weather: if cavok { Vec::new() } else { weather },
+            sky: if cavok {
+                Vec::new()
+            } else {
+                encode_sky(record, style)
+            },
+            temperature: record.tmp().air_temperature().map(|t| {
+                let dew_point = record.dew().dew_point_temperature();
+                format!(
+                    "{}/{}",
+                    encode_temperature(t),
+                    dew_point.map_or(String::new(), encode_temperature)
+                )
+            }),
+            altimeter: record
+                .ma1()
+                .and_then(|ma1| ma1.altimeter_setting())
+                .map(|a| encode_altimeter(a, style)),
+        }
+    }
+}
+
+impl fmt::Display for Metar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.report,
+            self.station,
+            self.date.format("%d%H%MZ")
+        )?;
+        let groups = self
+            .wind
+            .iter()
+            .chain(&self.visibility)
+            .chain(&self.weather)
+            .chain(&self.sky)
+            .chain(&self.temperature)
+            .chain(&self.altimeter);
+        for group in groups {
+            write!(f, " {}", group)?;
+        }
+        Ok(())
+    }
+}
+
+/// The `dddffGggKT` wind group, `00000KT` when calm and `VRBffKT` when variable.
+/// The gust in m/s is only included when it exceeds the wind speed.
+pub fn encode_wind(wind: &Wind, gust: Option<f64>) -> Option<String> {
+    if wind.is_calm() {
+        return Some("00000KT".to_string());
+    }
+    let speed = wind.speed_rate()?;
+    let direction = if wind.is_variable() {
+        "VRB".to_string()
+    } else {
+        let direction = (wind.direction_angle()? as f64 / 10.0).round() as i32 * 10;
+        format!("{:03}", if direction == 0 { 360 } else { direction })
+    };
+    let knots = (speed * KNOTS_PER_MS).round() as i32;
+    let gust = match gust.map(|g| (g * KNOTS_PER_MS).round() as i32) {
+        Some(g) if g > knots => format!("G{:02}", g),
+        _ => String::new(),
+    };
+    Some(format!("{}{:02}{}KT", direction, knots, gust))
+}
+
+/// The visibility group for a distance in meters.
+pub fn encode_visibility(distance: i32, style: MetarStyle) -> String {
+    match style {
+        MetarStyle::Icao => {
+            let step = match distance {
+                d if d < 800 => 50,
+                d if d < 5000 => 100,
+                _ => 1000,
+            };
+            format!("{:04}", (distance / step * step).min(9999))
+        }
+        MetarStyle::UnitedStates => {
+            const MILES: [(f64, &str); 28] = [
+                (0.0, "0"),
+                (0.0625, "1/16"),
+                (0.125, "1/8"),
+                (0.1875, "3/16"),
+                (0.25, "1/4"),
+                (0.3125, "5/16"),
+                (0.375, "3/8"),
+                (0.5, "1/2"),
+                (0.625, "5/8"),
+                (0.75, "3/4"),
+                (0.875, "7/8"),
+                (1.0, "1"),
+                (1.125, "1 1/8"),
+                (1.25, "1 1/4"),
+                (1.375, "1 3/8"),
+                (1.5, "1 1/2"),
+                (1.625, "1 5/8"),
+                (1.75, "1 3/4"),
+                (1.875, "1 7/8"),
+                (2.0, "2"),
+                (2.25, "2 1/4"),
+                (2.5, "2 1/2"),
+                (2.75, "2 3/4"),
+                (3.0, "3"),
+                (4.0, "4"),
+                (5.0, "5"),
+                (6.0, "6"),
+                (7.0, "7"),
+            ];
+            let miles = distance as f64 / METERS_PER_MILE;
+            if miles >= 9.5 {
+                return "10SM".to_string();
+            }
+            if miles >= 7.5 {
+                return format!("{}SM", miles.round());
+            }
+            let (_, text) = MILES
+                .iter()
+                .min_by(|a, b| (a.0 - miles).abs().total_cmp(&(b.0 - miles).abs()))
+                .unwrap_or(&MILES[0]);
+            format!("{}SM", text)
+        }
+    }
+}
+
+/// A whole degree temperature, `M` marking negative values.
+pub fn encode_temperature(temperature: f64) -> String {
+    let rounded = temperature.round() as i32;
+    if temperature < 0.0 {
+        format!("M{:02}", rounded.abs())
+    } else {
+        format!("{:02}", rounded)
+    }
+}
+
+/// The altimeter group for a setting in hPa.
+pub fn encode_altimeter(altimeter_setting: f64, style: MetarStyle) -> String {
+    match style {
+        MetarStyle::Icao => format!("Q{:04}", altimeter_setting.floor() as i32),
+        MetarStyle::UnitedStates => format!(
+            "A{:04}",
+            (altimeter_setting / HPA_PER_INHG * 100.0).round() as i32
+        ),
+    }
+}
+
+// METAR abbreviations in the order of the ISD `AUX` code tables, which start at code 1.
+const DESCRIPTORS: [&str; 8] = ["MI", "PR", "BC", "DR", "BL", "SH", "TS", "FZ"];
+const PRECIPITATION: [&str; 9] = ["DZ", "RA", "SN", "SG", "IC", "PL", "GR", "GS", "UP"];
+const OBSCURATIONS: [&str; 8] = ["BR", "FG", "FU", "VA", "DU", "SA", "HZ", "PY"];
+const OTHER_PHENOMENA: [&str; 5] = ["PO", "SQ", "FC", "SS", "DS"];
+
+fn abbreviation(table: &[&'static str], code: &str) -> &'static str {
+    code.parse::<usize>()
+        .ok()
+        .and_then(|code| code.checked_sub(1))
+        .and_then(|i| table.get(i))
+        .copied()
+        .unwrap_or("")
+}
+
+/// The METAR weather group of an `AUX` element, `None` when nothing is reported.
+fn encode_aux(aux: &AUX) -> Option<String> {
+    let intensity = match aux.intensity_code() {
+        "1" => "-",
+        "3" => "+",
+        "4" => "VC",
+        _ => "",
+    };
+    let descriptor = abbreviation(&DESCRIPTORS, aux.descriptor_code());
+    let phenomena = format!(
+        "{}{}{}",
+        abbreviation(&PRECIPITATION, aux.precipitation_code()),
+        abbreviation(&OBSCURATIONS, aux.obscuration_code()),
+        abbreviation(&OTHER_PHENOMENA, aux.weather_phenomena_code())
+    );
+    if phenomena.is_empty() && !matches!(descriptor, "TS" | "SH") {
+        return None;
+    }
+    Some(format!("{}{}{}", intensity, descriptor, phenomena))
+}
+
+/// The METAR weather group for a WMO present weather (ww) code, `None` for codes that
+/// describe no current weather at the station.
+pub fn present_weather(code: &str) -> Option<&'static str> {
+    let group = match code.parse::<u8>().ok()? {
+        4 => "FU",
+        5 => "HZ",
+        6 => "DU",
+        7 => "BLDU",
+        8 => "PO",
+        10 => "BR",
+        11 => "BCFG",
+        12 => "MIFG",
+        17 => "TS",
+        18 => "SQ",
+        19 => "FC",
+        30..=35 => "DS",
+        36 => "DRSN",
+        37 => "+DRSN",
+        38 => "BLSN",
+        39 => "+BLSN",
+        40 => "VCFG",
+        41 => "BCFG",
+        42..=47 => "FG",
+        48 | 49 => "FZFG",
+        50 | 51 => "-DZ",
+        52 | 53 => "DZ",
+        54 | 55 => "+DZ",
+        56 => "-FZDZ",
+        57 => "FZDZ",
+        58 => "-DZRA",
+        59 => "DZRA",
+        60 | 61 => "-RA",
+        62 | 63 => "RA",
+        64 | 65 => "+RA",
+        66 => "-FZRA",
+        67 => "FZRA",
+        68 => "-RASN",
+        69 => "RASN",
+        70 | 71 => "-SN",
+        72 | 73 => "SN",
+        74 | 75 => "+SN",
+        76 => "IC",
+        77 => "SG",
+        78 => "SN",
+        79 => "PL",
+        80 => "-SHRA",
+        81 => "SHRA",
+        82 => "+SHRA",
+        83 => "-SHRASN",
+        84 => "SHRASN",
+        85 => "-SHSN",
+        86 => "SHSN",
+        87 => "-SHGS",
+        88 => "SHGS",
+        89 => "-SHGR",
+        90 => "SHGR",
+        91 => "-RA",
+        92 => "RA",
+        93 => "-SN",
+        94 => "SN",
+        95 => "TSRA",
+        96 | 99 => "TSGR",
+        97 => "+TSRA",
+        98 => "TSDS",
+        _ => return None,
+    };
+    Some(group)
+}
+
+/// Cloud heights are reported in hundreds of feet.
+fn encode_height(height: i32) -> String {
+    format!(
+        "{:03}",
+        (height as f64 * FEET_PER_METER / 100.0).round() as i32
+    )
+}
+
+/// Sky condition from the `GAX` layers, else from the `GDX` summation layers.
+fn encode_sky(record: &Record, style: MetarStyle) -> Vec<String> {
+    let clear = match style {
+        MetarStyle::Icao => "NSC",
+        MetarStyle::UnitedStates => "CLR",
+    };
+    let layers: Vec<String> = record
+        .gax()
+        .filter_map(|ga| encode_gax(ga, clear))
+        .collect();
+    if !layers.is_empty() {
+        return layers;
+    }
+    record
+        .gdx()
+        .filter_map(|gd| encode_gdx(gd, clear))
+        .collect()
+}
+
+fn encode_gax(layer: &GAX, clear: &str) -> Option<String> {
+    let cover = match layer.coverage_code() {
+        "00" => return Some(clear.to_string()),
+        "01" | "02" => "FEW",
+        "03" | "04" | "11" | "12" | "13" => "SCT",
+        "05" | "06" | "07" | "14" | "15" | "16" => "BKN",
+        "08" | "17" | "18" | "19" => "OVC",
+        "09" => "VV",
+        _ => return None,
+    };
+    let height = match layer.base_height() {
+        Some(height) => encode_height(height),
+        None if cover == "VV" => "///".to_string(),
+        None => return None,
+    };
+    let cloud_type = match layer.cloud_type_code() {
+        "09" => "CB",
+        "12" => "TCU",
+        _ => "",
+    };
+    Some(format!("{}{}{}", cover, height, cloud_type))
+}
+
+fn encode_gdx(layer: &GDX, clear: &str) -> Option<String> {
+    let cover = match layer.coverage_code() {
+        "0" => return Some(clear.to_string()),
+        "1" => "FEW",
+        "2" => "SCT",
+        "3" => "BKN",
+        "4" => "OVC",
+        "5" => "VV",
+        _ => return None,
+    };
+    let height = match layer.height_dimension() {
+        Some(height) => encode_height(height),
+        None if cover == "VV" => "///".to_string(),
+        None => return None,
+    };
+    Some(format!("{}{}", cover, height))
+}
+
+/// A METAR decoded into the ISD field types, e.g. from the `MET` remark. Groups the
+/// report does not have, or that could not be decoded, are `None` or empty.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, PartialEq, Default)]
+pub struct DecodedMetar {
+    /// `METAR` or `SPECI`.
+    pub report: String,
+    pub station: String,
+    /// The day of the month of the observation.
+    pub day: Option<u32>,
+    pub time: Option<NaiveTime>,
+    pub wind: Option<Wind>,
+    pub gust: Option<OC1>,
+    pub visibility: Option<Visibility>,
+    pub weather: Vec<AUX>,
+    pub sky: Vec<GAX>,
+    /// The lowest broken, overcast or obscured layer, unlimited when the sky is reported
+    /// without one.
+    pub ceiling: Option<Ceiling>,
+    pub temperature: Option<Temperature>,
+    pub dew: Option<Dew>,
+    /// Whether the temperature and dew point are the tenths from the `T` remark group.
+    pub precise_temperature: bool,
+    pub altimeter: Option<MA1>,
+    /// From the `SLP` remark group.
+    pub sea_level_pressure: Option<SeaLevelPressure>,
+    /// The text after `RMK`.
+    pub remarks: Option<String>,
+}
+
+impl DecodedMetar {
+    /// Decodes the `MET` remark of a record, `None` when it has none or it can't be decoded.
+    pub fn from_record(record: &Record) -> Option<DecodedMetar> {
+        record.remark("MET")?.parse().ok()
+    }
+
+    /// The elements where the record and the METAR differ by more than the METAR's
+    /// rounding, or where only one of them has a value. An element with only a METAR
+    /// value is one NCEI dropped.
+    pub fn compare(&self, record: &Record) -> Vec<Discrepancy> {
+        let temperature_tolerance = if self.precise_temperature { 0.05 } else { 0.5 };
+        let wind = self.wind.as_ref();
+        let elements = [
+            (
+                "wind_direction",
+                record.wnd().direction_angle().map(f64::from),
+                wind.and_then(|w| w.direction_angle()).map(f64::from),
+                5.0,
+            ),
+            (
+                "wind_speed",
+                record.wnd().speed_rate(),
+                wind.and_then(|w| w.speed_rate()),
+                0.3,
+            ),
+            (
+                "gust",
+                record.oc1().and_then(|oc1| oc1.speed_rate()),
+                self.gust.as_ref().and_then(|oc1| oc1.speed_rate()),
+                0.3,
+            ),
+            (
+                "visibility",
+                record.vis().distance().map(f64::from),
+                self.visibility
+                    .as_ref()
+                    .and_then(|v| v.distance())
+                    .map(f64::from),
+                50.0,
+            ),
+            (
+                "ceiling",
+                record.cig().height().map(f64::from),
+                self.ceiling
+                    .as_ref()
+                    .and_then(|c| c.height())
+                    .map(f64::from),
+                31.0,
+            ),
+            (
+                "air_temperature",
+                record.tmp().air_temperature(),
+                self.temperature.as_ref().and_then(|t| t.air_temperature()),
+                temperature_tolerance,
+            ),
+            (
+                "dew_point_temperature",
+                record.dew().dew_point_temperature(),
+                self.dew.as_ref().and_then(|d| d.dew_point_temperature()),
+                temperature_tolerance,
+            ),
+            (
+                "altimeter_setting",
+                record.ma1().and_then(|ma1| ma1.altimeter_setting()),
+                self.altimeter
+                    .as_ref()
+                    .and_then(|ma1| ma1.altimeter_setting()),
+                0.2,
+            ),
+            (
+                "sea_level_pressure",
+                record.slp().pressure(),
+                self.sea_level_pressure.as_ref().and_then(|p| p.pressure()),
+                0.05,
+            ),
+        ];
+        discrepancies(elements)
+    }
+
+    fn decode_group(&mut self, token: &str, whole_miles: Option<f64>) {
+        if let Some((wind, gust)) = decode_wind(token) {
+            self.wind = Some(wind);
+            self.gust = gust;
+        } else if let Some(distance) = decode_visibility(token, whole_miles) {
+            self.visibility = format!("{:06},1,N,1", distance).parse().ok();
+        } else if let Some(layer) = decode_sky(token) {
+            self.sky.push(layer);
+        } else if let Some((temperature, dew_point)) = decode_temperatures(token) {
+            self.temperature = format!("{:+05},1", temperature * 10).parse().ok();
+            self.dew = dew_point.and_then(|td| format!("{:+05},1", td * 10).parse().ok());
+        } else if let Some(altimeter) = decode_altimeter(token) {
+            self.altimeter = format!("{:05},1,99999,9", altimeter).parse().ok();
+        } else if let Some(weather) = decode_weather(token) {
+            self.weather.extend(weather);
+        }
+    }
+
+    fn decode_remarks(&mut self, tokens: &[&str]) {
+        for token in tokens {
+            if let Some(pressure) = token
+                .strip_prefix("SLP")
+                .filter(|p| p.len() == 3)
+                .and_then(|p| p.parse::<i32>().ok())
+            {
+                let tenths = if pressure < 500 { 10000 } else { 9000 } + pressure;
+                self.sea_level_pressure = format!("{:05},1", tenths).parse().ok();
+            } else if let Some(values) = token
+                .strip_prefix('T')
+                .filter(|t| t.len() == 8 && t.bytes().all(|b| b.is_ascii_digit()))
+            {
+                let tenths = |s: &str| -> Option<i32> {
+                    let value: i32 = s[1..].parse().ok()?;
+                    match &s[..1] {
+                        "0" => Some(value),
+                        "1" => Some(-value),
+                        _ => None,
+                    }
+                };
+                if let (Some(t), Some(td)) = (tenths(&values[..4]), tenths(&values[4..])) {
+                    self.temperature = format!("{:+05},1", t).parse().ok();
+                    self.dew = format!("{:+05},1", td).parse().ok();
+                    self.precise_temperature = true;
+                }
+            }
+        }
+        self.remarks = Some(tokens.join(" "));
+    }
+
+    fn decode_ceiling(&mut self, cavok: bool) {
+        if cavok {
+            self.ceiling = "22000,1,9,Y".parse().ok();
+            return;
+        }
+        if self.sky.is_empty() {
+            return;
+        }
+        let height = self
+            .sky
+            .iter()
+            .filter(|layer| matches!(layer.coverage_code(), "07" | "08" | "09"))
+            .filter_map(|layer| layer.base_height())
+            .min()
+            .unwrap_or(22000);
+        self.ceiling = format!("{:05},1,9,N", height).parse().ok();
+    }
+}
+
+impl FromStr for DecodedMetar {
+    type Err = &'static str;
+
+    /// Decodes METAR text. Anything before the station and time groups, such as the
+    /// timestamp NCEI puts at the start of the `MET` remark, is skipped, as are trend forecasts.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+        let time_index = tokens
+            .iter()
+            .position(|t| is_time_group(t))
+            .filter(|i| *i > 0)
+            .ok_or("no station and time groups")?;
+        let time = tokens[time_index];
+        let mut metar = DecodedMetar {
+            report: match tokens[..time_index].iter().rev().nth(1) {
+                Some(&"SPECI") => "SPECI",
+                _ => "METAR",
+            }
+            .to_string(),
+            station: tokens[time_index - 1].to_string(),
+            day: time[..2].parse().ok(),
+            time: NaiveTime::from_hms_opt(
+                time[2..4].parse().unwrap_or(99),
+                time[4..6].parse().unwrap_or(99),
+                0,
+            ),
+            ..Default::default()
+        };
+
+        let mut cavok = false;
+        let mut trend = false;
+        let mut whole_miles = None;
+        let mut rest = tokens[time_index + 1..].iter();
+        while let Some(token) = rest.next() {
+            match *token {
+                "RMK" => {
+                    let remarks: Vec<&str> = rest.copied().collect();
+                    metar.decode_remarks(&remarks);
+                    break;
+                }
+                "BECMG" | "TEMPO" | "NOSIG" => trend = true,
+                _ if trend => {}
+                "CAVOK" => {
+                    cavok = true;
+                    metar.visibility = "010000,1,N,1".parse().ok();
+                }
+                _ if token.len() <= 2 && token.bytes().all(|b| b.is_ascii_digit()) => {
+                    whole_miles = token.parse().ok();
+                    continue;
+                }
+                _ => metar.decode_group(token, whole_miles),
+            }
+            whole_miles = None;
+        }
+        metar.decode_ceiling(cavok);
+        Ok(metar)
+    }
+}
+
+/// `DDHHMMZ`.
+fn is_time_group(token: &str) -> bool {
+    token.len() == 7 && token.ends_with('Z') && token[..6].bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// The wind and gust from a `dddffGggKT` group, also accepting `MPS` and `KMH` units.
+fn decode_wind(token: &str) -> Option<(Wind, Option<OC1>)> {
+    let (body, to_ms) = if let Some(body) = token.strip_suffix("KT") {
+        (body, 1.0 / KNOTS_PER_MS)
+    } else if let Some(body) = token.strip_suffix("MPS") {
+        (body, 1.0)
+    } else {
+        (token.strip_suffix("KMH")?, 1.0 / 3.6)
+    };
+    if body.len() < 5 || !body.is_ascii() {
+        return None;
+    }
+    let (direction, speeds) = body.split_at(3);
+    let (speed, gust) = match speeds.split_once('G') {
+        Some((speed, gust)) => (speed, Some(gust)),
+        None => (speeds, None),
+    };
+    if !is_digits(speed) || !gust.is_none_or(is_digits) {
+        return None;
+    }
+    let tenths = |s: &str| (s.parse::<f64>().unwrap_or(0.0) * to_ms * 10.0).round() as i32;
+    let speed = tenths(speed);
+    let (direction, type_code) = match direction {
+        "VRB" => ("999", "V"),
+        "000" if speed == 0 => ("999", "C"),
+        d if is_digits(d) => (d, "N"),
+        _ => return None,
+    };
+    let wind = format!("{},1,{},{:04},1", direction, type_code, speed)
+        .parse()
+        .ok()?;
+    let gust = gust.and_then(|g| format!("{:04},1", tenths(g)).parse().ok());
+    Some((wind, gust))
+}
+
+/// The visibility in meters of a `9999` or `10SM` group. `whole_miles` is the preceding
+/// whole number of a `1 1/2SM` group.
+fn decode_visibility(token: &str, whole_miles: Option<f64>) -> Option<i32> {
+    if let Some(miles) = token.strip_suffix("SM") {
+        let miles = miles.trim_start_matches(['M', 'P']);
+        let miles = match miles.split_once('/') {
+            Some((numerator, denominator)) => {
+                numerator.parse::<f64>().ok()? / denominator.parse::<f64>().ok()?
+            }
+            None => miles.parse::<f64>().ok()?,
+        };
+        return Some(((miles + whole_miles.unwrap_or(0.0)) * METERS_PER_MILE).round() as i32);
+    }
+    let (meters, suffix) = token.split_at_checked(4)?;
+    if !is_digits(meters)
+        || !matches!(
+            suffix,
+            "" | "NDV" | "N" | "NE" | "E" | "SE" | "S" | "SW" | "W" | "NW"
+        )
+    {
+        return None;
+    }
+    match meters.parse().ok()? {
+        9999 => Some(10000),
+        meters => Some(meters),
+    }
+}
+
+/// A `FEW025CB` style sky condition layer.
+fn decode_sky(token: &str) -> Option<GAX> {
+    if matches!(token, "CLR" | "SKC" | "NSC" | "NCD") {
+        return "00,1,+99999,9,99,9".parse().ok();
+    }
+    let (coverage, rest) = [
+        ("FEW", "02"),
+        ("SCT", "04"),
+        ("BKN", "07"),
+        ("OVC", "08"),
+        ("VV", "09"),
+    ]
+    .iter()
+    .find_map(|(cover, code)| token.strip_prefix(cover).map(|rest| (*code, rest)))?;
+    let (height, cloud_type) = rest.split_at_checked(3)?;
+    let height = match height {
+        "///" => 99999,
+        h if is_digits(h) => (h.parse::<f64>().ok()? * 100.0 / FEET_PER_METER).round() as i32,
+        _ => return None,
+    };
+    let cloud_type = match cloud_type {
+        "CB" => "09",
+        "TCU" => "12",
+        "" | "///" => "99",
+        _ => return None,
+    };
+    format!("{},1,{:+06},1,{},1", coverage, height, cloud_type)
+        .parse()
+        .ok()
+}
+
+/// The whole degree temperature and dew point of a `06/M04` group.
+fn decode_temperatures(token: &str) -> Option<(i32, Option<i32>)> {
+    let decode = |s: &str| -> Option<i32> {
+        let (sign, digits) = match s.strip_prefix('M') {
+            Some(digits) => (-1, digits),
+            None => (1, s),
+        };
+        if digits.len() != 2 || !is_digits(digits) {
+            return None;
+        }
+        Some(sign * digits.parse::<i32>().ok()?)
+    };
+    let (temperature, dew_point) = token.split_once('/')?;
+    Some((decode(temperature)?, decode(dew_point)))
+}
+
+/// The altimeter setting in tenths of hPa of an `A2992` or `Q1013` group.
+fn decode_altimeter(token: &str) -> Option<i32> {
+    let (unit, value) = token.split_at_checked(1)?;
+    if value.len() != 4 || !is_digits(value) {
+        return None;
+    }
+    let value: f64 = value.parse().ok()?;
+    match unit {
+        "A" => Some((value / 100.0 * HPA_PER_INHG * 10.0).round() as i32),
+        "Q" => Some((value * 10.0) as i32),
+        _ => None,
+    }
+}
+
+/// The `AUX` elements of a `-SHRASN` style weather group, one per phenomenon with the
+/// combination indicator linking them.
+fn decode_weather(token: &str) -> Option<Vec<AUX>> {
+    if !token.is_ascii() {
+        return None;
+    }
+    let (intensity, rest) = if let Some(rest) = token.strip_prefix('-') {
+        ("1", rest)
+    } else if let Some(rest) = token.strip_prefix('+') {
+        ("3", rest)
+    } else if let Some(rest) = token.strip_prefix("VC") {
+        ("4", rest)
+    } else {
+        ("2", token)
+    };
+    let (descriptor, rest) = match DESCRIPTORS.iter().position(|d| rest.starts_with(d)) {
+        Some(i) => (i + 1, &rest[2..]),
+        None => (0, rest),
+    };
+    if rest.len() % 2 != 0 {
+        return None;
+    }
+    let phenomena: Vec<&str> = (0..rest.len())
+        .step_by(2)
+        .map(|i| &rest[i..i + 2])
+        .collect();
+    if phenomena.is_empty() {
+        // thunderstorms and showers can be reported on their own
+        return match DESCRIPTORS.get(descriptor.wrapping_sub(1)) {
+            Some(&"TS") | Some(&"SH") => {
+                Some(vec![format!("{},{},00,0,0,1,1", intensity, descriptor)
+                    .parse()
+                    .ok()?])
+            }
+            _ => None,
+        };
+    }
+    let code = |table: &[&str], phenomenon: &str| {
+        table.iter().position(|p| *p == phenomenon).map(|i| i + 1)
+    };
+    phenomena
+        .iter()
+        .enumerate()
+        .map(|(n, phenomenon)| {
+            let (precipitation, obscuration, other) =
+                if let Some(c) = code(&PRECIPITATION, phenomenon) {
+                    (c, 0, 0)
+                } else if let Some(c) = code(&OBSCURATIONS, phenomenon) {
+                    (0, c, 0)
+                } else {
+                    (0, 0, code(&OTHER_PHENOMENA, phenomenon)?)
+                };
+            let combination = match (phenomena.len(), n) {
+                (1, _) => 1,
+                (_, 0) => 2,
+                _ => 3,
+            };
+            let descriptor = if n == 0 { descriptor } else { 0 };
+            format!(
+                "{},{},{:02},{},{},{},1",
+                intensity, descriptor, precipitation, obscuration, other, combination
+            )
+            .parse()
+            .ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::{record, records};
+
+    #[test]
+    fn test_encode_metar() {
+        let records = records(&[
+            &[
+                ("CALL_SIGN", "KLGA"),
+                ("MA1", "10098,1,10096,1"),
+                ("GA1", "02,1,+07620,1,99,9"),
+            ],
+            &[
+                ("DATE", "2020-01-01T01:51:00"),
+                ("REPORT_TYPE", "FM-16"),
+                ("CALL_SIGN", "KLGA"),
+                ("WND", "999,9,V,0021,1"),
+                ("CIG", "00457,1,9,N"),
+                ("VIS", "004023,1,9,9"),
+                ("TMP", "-0011,1"),
+                ("DEW", "-0022,1"),
+                ("MA1", "10098,1,10096,1"),
+                ("AU1", "1,0,02,0,0,2,1"),
+                ("AU2", "2,0,03,0,0,3,1"),
+                ("GA1", "04,1,+00457,1,99,9"),
+                ("GA2", "08,1,+00914,1,09,9"),
+            ],
+        ]);
+        let metar = Metar::from_record(&records[0], "KLGA", MetarStyle::UnitedStates);
+        assert_eq!(
+            metar.to_string(),
+            "METAR KLGA 010051Z 27009KT 10SM FEW250 06/M04 A2982"
+        );
+        let metar = Metar::from_record(&records[1], "KLGA", MetarStyle::Icao);
+        assert_eq!(
+            metar.to_string(),
+            "SPECI KLGA 010151Z VRB04KT 4000 -RASN SCT015 OVC030CB M01/M02 Q1009"
+        );
+    }
+
+    #[test]
+    fn test_decode_metar() {
+        let record = record(&[
+            ("CALL_SIGN", "KLGA"),
+            ("MA1", "10098,1,10096,1"),
+            (
+                "REM",
+                "MET10612/31/19 19:51:02 METAR KLGA 010051Z 27009KT 10SM FEW250 06/M04 A2982 RMK AO2 SLP100 T00611044 $ (KLGA)",
+            ),
+        ]);
+        let metar = DecodedMetar::from_record(&record).unwrap();
+        assert_eq!(metar.station, "KLGA");
+        assert_eq!(metar.day, Some(1));
+        assert_eq!(metar.wind.as_ref().unwrap().direction_angle(), Some(270));
+        assert!(metar.precise_temperature);
+        assert_eq!(metar.compare(&record), vec![]);
+
+        let metar: DecodedMetar =
+            "METAR EGLL 011250Z 24015G25KT 1 1/2SM -RASN BKN012CB OVC030 M01/M03 Q0998 NOSIG"
+                .parse()
+                .unwrap();
+        assert_eq!(metar.visibility.unwrap().distance(), Some(2414));
+        assert_eq!(metar.weather.len(), 2);
+        assert_eq!(metar.weather[1].combination_indicator_code(), "3");
+        assert_eq!(metar.sky.len(), 2);
+        assert_eq!(metar.ceiling.unwrap().height(), Some(366));
+        assert!((metar.gust.unwrap().speed_rate().unwrap() - 12.9).abs() < 1e-9);
+        assert_eq!(metar.temperature.unwrap().air_temperature(), Some(-1.0));
+        assert_eq!(metar.altimeter.unwrap().altimeter_setting(), Some(998.0));
+        assert!("27009KT 10SM".parse::<DecodedMetar>().is_err());
+
+        // remark text that only looks like a temperature group is ignored
+        let metar: DecodedMetar =
+            "METAR KLGA 010051Z 27009KT 10SM FEW250 06/M04 A2982 RMK T006é144 T0061é44"
+                .parse()
+                .unwrap();
+        assert!(!metar.precise_temperature);
+        assert_eq!(metar.temperature.unwrap().air_temperature(), Some(6.0));
+        assert_eq!(metar.remarks.as_deref(), Some("T006é144 T0061é44"));
+    }
+
+    #[test]
+    fn test_present_weather() {
+        // slight or moderate, then heavy, drifting snow below eye level and blowing snow
+        // above it
+        assert_eq!(present_weather("36"), Some("DRSN"));
+        assert_eq!(present_weather("37"), Some("+DRSN"));
+        assert_eq!(present_weather("38"), Some("BLSN"));
+        assert_eq!(present_weather("39"), Some("+BLSN"));
+        assert_eq!(present_weather("00"), None);
+    }
+
+    #[test]
+    fn test_encode_visibility() {
+        assert_eq!(encode_visibility(1207, MetarStyle::UnitedStates), "3/4SM");
+        assert_eq!(encode_visibility(2414, MetarStyle::UnitedStates), "1 1/2SM");
+        assert_eq!(encode_visibility(12000, MetarStyle::Icao), "9999");
+        assert_eq!(encode_visibility(650, MetarStyle::Icao), "0650");
+    }
+}
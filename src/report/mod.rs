@@ -0,0 +1,35 @@
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+
+pub mod metar;
+pub mod synop;
+
+/// An element where a `Record` and a decoded report disagree.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Discrepancy {
+    pub element: &'static str,
+    pub record: Option<f64>,
+    pub report: Option<f64>,
+}
+
+/// The `(element, record, report, tolerance)` values that differ by more than the
+/// tolerance, or where only one of the two has a value.
+pub(crate) fn discrepancies<I>(elements: I) -> Vec<Discrepancy>
+where
+    I: IntoIterator<Item = (&'static str, Option<f64>, Option<f64>, f64)>,
+{
+    elements
+        .into_iter()
+        .filter(|(_, record, report, tolerance)| match (record, report) {
+            (Some(r), Some(m)) => (r - m).abs() > tolerance + 1e-9,
+            (None, None) => false,
+            _ => true,
+        })
+        .map(|(element, record, report, _)| Discrepancy {
+            element,
+            record,
+            report,
+        })
+        .collect()
+}
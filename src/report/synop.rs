@@ -0,0 +1,495 @@
+use crate::model::Record;
+use crate::report::{discrepancies, Discrepancy};
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::str::FromStr;
+
+const MS_PER_KNOT: f64 = 1.0 / 1.943_844;
+
+/// The unit of the SYNOP wind speed, from the `iw` indicator of section 0.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindSpeedUnit {
+    MetersPerSecond,
+    Knots,
+}
+
+/// Section 0 `YYGGiw`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SynopHeader {
+    pub day: u32,
+    pub hour: u32,
+    pub wind_speed_unit: Option<WindSpeedUnit>,
+}
+
+/// The `iRixhVV` group.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Indicators {
+    /// `iR`, where the precipitation groups are included.
+    pub precipitation_indicator: Option<u8>,
+    /// `ix`, the station type and whether the weather group is included.
+    pub station_type: Option<u8>,
+    /// `h`, the code for the height of the lowest cloud base.
+    pub cloud_base_code: Option<u8>,
+    /// `VV` as meters.
+    pub visibility: Option<f64>,
+}
+
+/// The `Nddff` group.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CloudWind {
+    /// Total cloud cover in oktas, 9 when the sky is obscured.
+    pub total_cover: Option<u8>,
+    /// Wind direction in degrees, `None` when calm or variable.
+    pub direction: Option<u16>,
+    pub calm: bool,
+    pub variable: bool,
+    /// Wind speed in the unit of the header, taken from the `00fff` group when over 99.
+    pub speed: Option<u16>,
+}
+
+/// The `5appp` group.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PressureTendency {
+    /// `a`, the WMO characteristic, `0`-`3` rising, `4` steady, `5`-`8` falling.
+    pub characteristic: u8,
+    /// The signed change over the last three hours in hPa.
+    pub change: f64,
+}
+
+/// A `6RRRt` group.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SynopPrecipitation {
+    /// Precipitation in mm, 0 for a trace.
+    pub amount: f64,
+    pub trace: bool,
+    /// The accumulation period in hours, `None` when not given.
+    pub period: Option<u8>,
+}
+
+/// The `7wwW1W2` group.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SynopWeather {
+    /// `ww`, the WMO present weather code.
+    pub present: Option<u8>,
+    pub past_1: Option<u8>,
+    pub past_2: Option<u8>,
+}
+
+/// The `8NhCLCMCH` group.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SynopClouds {
+    /// `Nh`, the cover of the low clouds, or the middle clouds without low clouds, in oktas.
+    pub low_cover: Option<u8>,
+    /// `CL`, `CM` and `CH` genus codes.
+    pub low: Option<u8>,
+    pub middle: Option<u8>,
+    pub high: Option<u8>,
+}
+
+/// A SYNOP (FM-12) report decoded from the `SYN` remark, sections 0, 1 and 3.
+/// Temperatures are in °C and pressures in hPa.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct Synop {
+    pub header: Option<SynopHeader>,
+    /// `IIiii`, the WMO block and station number.
+    pub station: Option<String>,
+    pub indicators: Option<Indicators>,
+    pub cloud_wind: Option<CloudWind>,
+    pub temperature: Option<f64>,
+    pub dew_point: Option<f64>,
+    /// From a `29UUU` group, in %.
+    pub relative_humidity: Option<f64>,
+    pub station_pressure: Option<f64>,
+    pub sea_level_pressure: Option<f64>,
+    pub pressure_tendency: Option<PressureTendency>,
+    /// The `6RRRt` groups of sections 1 and 3.
+    pub precipitation: Vec<SynopPrecipitation>,
+    pub weather: Option<SynopWeather>,
+    pub clouds: Option<SynopClouds>,
+    /// From section 3.
+    pub maximum_temperature: Option<f64>,
+    pub minimum_temperature: Option<f64>,
+}
+
+/// The value of a code figure, `None` when it has a `/` for missing.
+fn digits(s: &str) -> Option<u32> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn digit(s: &str, i: usize) -> Option<u8> {
+    s.get(i..i + 1).and_then(digits).map(|d| d as u8)
+}
+
+/// A `sTTT` temperature in tenths, `s` 0 for positive and 1 for negative.
+fn temperature(s: &str) -> Option<f64> {
+    let value = digits(&s[1..])? as f64 / 10.0;
+    match &s[..1] {
+        "0" => Some(value),
+        "1" => Some(-value),
+        _ => None,
+    }
+}
+
+/// A `PPPP` pressure in tenths of hPa with the thousands digit left out.
+fn pressure(s: &str) -> Option<f64> {
+    let tenths = digits(s)?;
+    let tenths = if tenths < 5000 {
+        tenths + 10000
+    } else {
+        tenths
+    };
+    Some(tenths as f64 / 10.0)
+}
+
+/// The `VV` horizontal visibility code in meters, taking the lower limit of each range.
+pub fn visibility(code: u8) -> Option<f64> {
+    let meters = match code {
+        0 => 0.0,
+        1..=50 => code as f64 * 100.0,
+        56..=80 => (code as f64 - 50.0) * 1000.0,
+        81..=89 => (code as f64 - 74.0) * 5000.0,
+        90 => 0.0,
+        91 => 50.0,
+        92 => 200.0,
+        93 => 500.0,
+        94 => 1000.0,
+        95 => 2000.0,
+        96 => 4000.0,
+        97 => 10000.0,
+        98 => 20000.0,
+        99 => 50000.0,
+        _ => return None,
+    };
+    Some(meters)
+}
+
+/// The accumulation period in hours of the `tR` code.
+fn precipitation_period(code: Option<u8>) -> Option<u8> {
+    match code? {
+        1 => Some(6),
+        2 => Some(12),
+        3 => Some(18),
+        4 => Some(24),
+        5 => Some(1),
+        6 => Some(2),
+        7 => Some(3),
+        8 => Some(9),
+        9 => Some(15),
+        _ => None,
+    }
+}
+
+fn precipitation(group: &str) -> Option<SynopPrecipitation> {
+    let amount = digits(&group[1..4])?;
+    let (amount, trace) = match amount {
+        990 => (0.0, true),
+        991..=999 => ((amount - 990) as f64 / 10.0, false),
+        _ => (amount as f64, false),
+    };
+    Some(SynopPrecipitation {
+        amount,
+        trace,
+        period: precipitation_period(digit(group, 4)),
+    })
+}
+
+impl Synop {
+    /// Decodes the `SYN` remark of a record, `None` when it has none or it can't be decoded.
+    pub fn from_record(record: &Record) -> Option<Synop> {
+        record.remark("SYN")?.parse().ok()
+    }
+
+    /// The wind speed in m/s, `None` when the header does not give the unit.
+    pub fn wind_speed(&self) -> Option<f64> {
+        let speed = self.cloud_wind.as_ref()?.speed? as f64;
+        match self.header.as_ref()?.wind_speed_unit? {
+            WindSpeedUnit::MetersPerSecond => Some(speed),
+            WindSpeedUnit::Knots => Some(speed * MS_PER_KNOT),
+        }
+    }
+
+    /// The elements where the record differs from the SYNOP, or where only one of them has
+    /// a value. Code figures are compared as numbers, and the present weather matches when
+    /// any `MW` group of the record has it.
+    pub fn compare(&self, record: &Record) -> Vec<Discrepancy> {
+        let code = |s: &str| digits(s).map(f64::from);
+        let md1 = record.md1();
+        let gf1 = record.gf1();
+        let tendency = self.pressure_tendency.as_ref();
+        let clouds = self.clouds.as_ref();
+
+        let mut elements = vec![
+            (
+                "air_temperature",
+                record.tmp().air_temperature(),
+                self.temperature,
+                0.05,
+            ),
+            (
+                "dew_point_temperature",
+                record.dew().dew_point_temperature(),
+                self.dew_point,
+                0.05,
+            ),
+            (
+                "sea_level_pressure",
+                record.slp().pressure(),
+                self.sea_level_pressure,
+                0.05,
+            ),
+            (
+                "pressure_tendency",
+                md1.and_then(|md1| code(md1.tendency_code())),
+                tendency.map(|t| t.characteristic as f64),
+                0.0,
+            ),
+            (
+                "pressure_change",
+                md1.and_then(|md1| md1.three_hour_quantity()),
+                tendency.map(|t| t.change.abs()),
+                0.05,
+            ),
+            (
+                "total_cloud_cover",
+                gf1.and_then(|gf1| code(gf1.total_coverage_code())),
+                self.cloud_wind
+                    .as_ref()
+                    .and_then(|c| c.total_cover)
+                    .map(f64::from),
+                0.0,
+            ),
+            (
+                "low_cloud_cover",
+                gf1.and_then(|gf1| code(gf1.low_total_coverage_code())),
+                clouds.and_then(|c| c.low_cover).map(f64::from),
+                0.0,
+            ),
+            (
+                "low_cloud_genus",
+                gf1.and_then(|gf1| code(gf1.low_cloud_genus_code())),
+                clouds.and_then(|c| c.low).map(f64::from),
+                0.0,
+            ),
+            (
+                "mid_cloud_genus",
+                gf1.and_then(|gf1| code(gf1.mid_cloud_genus_code())),
+                clouds.and_then(|c| c.middle).map(f64::from),
+                0.0,
+            ),
+            (
+                "high_cloud_genus",
+                gf1.and_then(|gf1| code(gf1.high_cloud_genus_code())),
+                clouds.and_then(|c| c.high).map(f64::from),
+                0.0,
+            ),
+        ];
+
+        let present = self.weather.as_ref().and_then(|w| w.present).map(f64::from);
+        let reported: Vec<f64> = record
+            .mwx()
+            .filter_map(|mw| code(mw.atmospheric_condition_code()))
+            .collect();
+        let matching = present.filter(|p| reported.contains(p));
+        elements.push((
+            "present_weather",
+            matching.or(reported.first().copied()),
+            present,
+            0.0,
+        ));
+
+        for synop in self.precipitation.iter().filter(|p| p.period.is_some()) {
+            let period = synop.period.map(|p| p as i8);
+            let depth = record
+                .aax()
+                .find(|aa| aa.period_quantity() == period)
+                .and_then(|aa| aa.depth_dimension());
+            elements.push(("precipitation", depth, Some(synop.amount), 0.05));
+        }
+
+        discrepancies(elements)
+    }
+
+    fn decode_section_1(&mut self, group: &str) {
+        match &group[..1] {
+            "1" => self.temperature = temperature(&group[1..]),
+            "2" if &group[1..2] == "9" => {
+                self.relative_humidity = digits(&group[2..]).map(f64::from)
+            }
+            "2" => self.dew_point = temperature(&group[1..]),
+            "3" => self.station_pressure = pressure(&group[1..]),
+            // 4a3hhh geopotential groups start with a digit other than 0 or 9
+            "4" if matches!(&group[1..2], "0" | "9") => {
+                self.sea_level_pressure = pressure(&group[1..])
+            }
+            "5" => {
+                if let (Some(characteristic), Some(change)) = (digit(group, 1), digits(&group[2..]))
+                {
+                    let change = change as f64 / 10.0;
+                    self.pressure_tendency = Some(PressureTendency {
+                        characteristic,
+                        change: if characteristic >= 5 { -change } else { change },
+                    });
+                }
+            }
+            "6" => self.precipitation.extend(precipitation(group)),
+            "7" => {
+                self.weather = Some(SynopWeather {
+                    present: group.get(1..3).and_then(digits).map(|d| d as u8),
+                    past_1: digit(group, 3),
+                    past_2: digit(group, 4),
+                })
+            }
+            "8" => {
+                self.clouds = Some(SynopClouds {
+                    low_cover: digit(group, 1),
+                    low: digit(group, 2),
+                    middle: digit(group, 3),
+                    high: digit(group, 4),
+                })
+            }
+            _ => {}
+        }
+    }
+
+    fn decode_section_3(&mut self, group: &str) {
+        match &group[..1] {
+            "1" => self.maximum_temperature = temperature(&group[1..]),
+            "2" => self.minimum_temperature = temperature(&group[1..]),
+            "6" => self.precipitation.extend(precipitation(group)),
+            _ => {}
+        }
+    }
+}
+
+impl FromStr for Synop {
+    type Err = &'static str;
+
+    /// Decodes a SYNOP from an optional `AAXX YYGGiw` section 0 and the `IIiii iRixhVV Nddff`
+    /// groups that start section 1. Groups that can't be decoded are skipped, as are
+    /// sections 2, 4 and 5.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut groups = s
+            .split_whitespace()
+            .map(|g| g.trim_end_matches('='))
+            .peekable();
+        let mut synop = Synop::default();
+
+        if groups.peek() == Some(&"AAXX") {
+            groups.next();
+            let header = groups.next().ok_or("missing YYGGiw group")?;
+            synop.header = Some(SynopHeader {
+                day: header
+                    .get(..2)
+                    .and_then(digits)
+                    .ok_or("invalid YYGGiw group")?,
+                hour: header
+                    .get(2..4)
+                    .and_then(digits)
+                    .ok_or("invalid YYGGiw group")?,
+                wind_speed_unit: match digit(header, 4) {
+                    Some(0 | 1) => Some(WindSpeedUnit::MetersPerSecond),
+                    Some(3 | 4) => Some(WindSpeedUnit::Knots),
+                    _ => None,
+                },
+            });
+        }
+
+        let station = groups.next().filter(|g| g.len() == 5 && g.is_ascii());
+        let indicators = groups.next().filter(|g| g.len() == 5 && g.is_ascii());
+        let cloud_wind = groups.next().filter(|g| g.len() == 5 && g.is_ascii());
+        let (station, indicators, cloud_wind) = match (station, indicators, cloud_wind) {
+            (Some(s), Some(i), Some(c)) => (s, i, c),
+            _ => return Err("missing IIiii iRixhVV Nddff groups"),
+        };
+        synop.station = digits(station).map(|_| station.to_string());
+        synop.indicators = Some(Indicators {
+            precipitation_indicator: digit(indicators, 0),
+            station_type: digit(indicators, 1),
+            cloud_base_code: digit(indicators, 2),
+            visibility: digits(&indicators[3..]).and_then(|v| visibility(v as u8)),
+        });
+        let direction = digits(&cloud_wind[1..3]);
+        let mut speed = digits(&cloud_wind[3..]).map(|s| s as u16);
+        if speed == Some(99) {
+            if let Some(extended) = groups.next_if(|g| g.len() == 5 && g.starts_with("00")) {
+                speed = digits(&extended[2..]).map(|s| s as u16);
+            }
+        }
+        synop.cloud_wind = Some(CloudWind {
+            total_cover: digit(cloud_wind, 0),
+            direction: direction
+                .filter(|d| (1..=36).contains(d))
+                .map(|d| d as u16 * 10),
+            calm: direction == Some(0) && speed == Some(0),
+            variable: direction == Some(99),
+            speed,
+        });
+
+        let mut section = 1;
+        for group in groups {
+            match group {
+                "222" | "444" | "555" => section = 0,
+                "333" => section = 3,
+                _ if group.len() != 5 || !group.is_ascii() => {}
+                _ if section == 1 => synop.decode_section_1(group),
+                _ if section == 3 => synop.decode_section_3(group),
+                _ => {}
+            }
+        }
+        Ok(synop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::record;
+
+    #[test]
+    fn test_decode_synop() {
+        let synop: Synop = "AAXX 01124 72503 41560 82710 10061 21044 30096 40100 52012 60051 70222 86840 333 10083 21011 69974"
+            .parse()
+            .unwrap();
+        assert_eq!(synop.station.as_deref(), Some("72503"));
+        assert_eq!(synop.indicators.as_ref().unwrap().visibility, Some(10000.0));
+        assert_eq!(synop.cloud_wind.as_ref().unwrap().direction, Some(270));
+        assert!((synop.wind_speed().unwrap() - 5.144).abs() < 1e-3);
+        assert_eq!(synop.temperature, Some(6.1));
+        assert_eq!(synop.dew_point, Some(-4.4));
+        assert_eq!(synop.station_pressure, Some(1009.6));
+        assert_eq!(synop.sea_level_pressure, Some(1010.0));
+        assert_eq!(synop.pressure_tendency.as_ref().unwrap().change, 1.2);
+        assert_eq!(synop.maximum_temperature, Some(8.3));
+        assert_eq!(synop.minimum_temperature, Some(-1.1));
+        assert_eq!(synop.precipitation.len(), 2);
+        assert!((synop.precipitation[1].amount - 0.7).abs() < 1e-9);
+        assert_eq!(synop.precipitation[1].period, Some(24));
+
+        let record = record(&[
+            ("DATE", "2020-01-01T12:00:00"),
+            ("SOURCE", "4"),
+            ("REPORT_TYPE", "FM-12"),
+            ("QUALITY_CONTROL", "V020"),
+            ("WND", "270,1,N,0051,1"),
+            ("AA1", "06,0050,9,1"),
+            ("MD1", "2,1,012,1,999,9"),
+            ("MW1", "02,1"),
+            ("GF1", "08,99,1,06,1,08,1,99999,9,04,1,01"),
+        ]);
+        let discrepancies = synop.compare(&record);
+        assert_eq!(discrepancies.len(), 2);
+        // the record has no 24 hour precipitation, and a different high cloud genus
+        assert_eq!(discrepancies[0].element, "high_cloud_genus");
+        assert_eq!(discrepancies[1].element, "precipitation");
+        assert_eq!(discrepancies[1].record, None);
+    }
+}
@@ -0,0 +1,333 @@
+use crate::series::resample::{Element, HourlyObservation, HourlyValue, Provenance};
+use crate::station::id::StationId;
+use chrono::{Duration, NaiveDateTime, Timelike};
+use serde::Serialize;
+
+/// The elements checked for gaps by default.
+pub const GAP_ELEMENTS: [Element; 6] = [
+    Element::Temperature,
+    Element::DewPoint,
+    Element::SeaLevelPressure,
+    Element::StationPressure,
+    Element::WindDirection,
+    Element::WindSpeed,
+];
+
+/// A run of hours without a value for an element.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Gap {
+    pub station: Option<StationId>,
+    pub element: Element,
+    /// The first and last missing hour.
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub hours: i64,
+}
+
+/// Whether the hour has no value for the element because none was reported, as opposed to
+/// calm and variable winds, whose reported wind speed comes without a direction.
+fn is_missing(observation: &HourlyObservation, element: Element) -> bool {
+    observation.get(element).is_none()
+        && !(element == Element::WindDirection && is_calm_or_variable(observation))
+}
+
+fn is_calm_or_variable(observation: &HourlyObservation) -> bool {
+    observation.wind_direction.is_none()
+        && observation
+            .wind_speed
+            .as_ref()
+            .is_some_and(|v| v.provenance == Provenance::Reported)
+}
+
+/// The gaps of each element within the hours spanned by each station's observations, which
+/// should be ordered by station and hour as returned by `series::resample::resample`. Hours
+/// that are not in the observations at all also count as missing, while calm and variable
+/// winds don't count as missing wind directions.
+pub fn find_gaps(observations: &[HourlyObservation], elements: &[Element]) -> Vec<Gap> {
+    let mut gaps = Vec::new();
+    for station in observations.chunk_by(|a, b| a.station == b.station) {
+        let (first, last) = match (station.first(), station.last()) {
+            (Some(first), Some(last)) => (first.hour, last.hour),
+            _ => continue,
+        };
+        for element in elements {
+            let mut gap = |start: NaiveDateTime, end: NaiveDateTime| {
+                if start <= end {
+                    gaps.push(Gap {
+                        station: station[0].station,
+                        element: *element,
+                        start,
+                        end,
+                        hours: (end - start).num_hours() + 1,
+                    });
+                }
+            };
+            let mut next = first;
+            for observation in station.iter().filter(|o| !is_missing(o, *element)) {
+                gap(next, observation.hour - Duration::hours(1));
+                next = observation.hour + Duration::hours(1);
+            }
+            gap(next, last);
+        }
+    }
+    gaps
+}
+
+/// How gaps between reported values are filled. Gaps of up to `linear_hours` missing hours
+/// are interpolated linearly, longer gaps of up to `diurnal_hours` follow the mean diurnal
+/// cycle of the `diurnal_days` around them, and longer gaps are left alone.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct GapFilling {
+    pub linear_hours: i64,
+    pub diurnal_hours: i64,
+    pub diurnal_days: i64,
+}
+
+impl Default for GapFilling {
+    fn default() -> GapFilling {
+        GapFilling {
+            linear_hours: 3,
+            diurnal_hours: 12,
+            diurnal_days: 15,
+        }
+    }
+}
+
+/// Interpolates from `a` to `b`, along the shorter arc for wind directions.
+fn interpolate(element: Element, a: f64, b: f64, fraction: f64) -> f64 {
+    if element == Element::WindDirection {
+        let difference = (b - a + 540.0) % 360.0 - 180.0;
+        let direction = (a + difference * fraction).rem_euclid(360.0);
+        if direction == 0.0 {
+            360.0
+        } else {
+            direction
+        }
+    } else {
+        a + (b - a) * fraction
+    }
+}
+
+/// The mean reported value at each hour of the day within `days` of `start` and `end`.
+fn diurnal_cycle(
+    observations: &[HourlyObservation],
+    element: Element,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    days: i64,
+) -> [Option<f64>; 24] {
+    let mut sums = [(0.0, 0); 24];
+    for observation in observations {
+        if observation.hour < start - Duration::days(days)
+            || observation.hour > end + Duration::days(days)
+        {
+            continue;
+        }
+        if let Some(value) = observation
+            .get(element)
+            .filter(|v| v.provenance == Provenance::Reported)
+        {
+            let sum = &mut sums[observation.hour.hour() as usize];
+            sum.0 += value.value;
+            sum.1 += 1;
+        }
+    }
+    sums.map(|(sum, count)| {
+        if count == 0 {
+            None
+        } else {
+            Some(sum / count as f64)
+        }
+    })
+}
+
+/// Fills the gaps of an element between reported values, marking the filled values with
+/// their `Provenance`, and returns how many values were filled. Gaps at the start or end of
+/// a station's observations are not filled, and neither are the diurnal cycles of wind
+/// direction nor the wind directions next to calm and variable winds. The observations
+/// should be ordered by station and hour, with every hour present, as returned by
+/// `series::resample::resample`.
+pub fn fill_gaps(
+    observations: &mut [HourlyObservation],
+    element: Element,
+    filling: &GapFilling,
+) -> usize {
+    let mut filled = 0;
+    for station in observations.chunk_by_mut(|a, b| a.station == b.station) {
+        // calm and variable winds end a gap without a direction to interpolate from
+        let reported: Vec<(usize, NaiveDateTime, Option<f64>)> = station
+            .iter()
+            .enumerate()
+            .filter_map(|(i, o)| match o.get(element) {
+                Some(v) if v.provenance == Provenance::Reported => Some((i, o.hour, Some(v.value))),
+                None if !is_missing(o, element) => Some((i, o.hour, None)),
+                _ => None,
+            })
+            .collect();
+        for pair in reported.windows(2) {
+            let ((i0, h0, v0), (i1, h1, v1)) = (pair[0], pair[1]);
+            let (Some(v0), Some(v1)) = (v0, v1) else {
+                continue;
+            };
+            let missing = (h1 - h0).num_hours() - 1;
+            if missing < 1 || i1 - i0 < 2 {
+                continue;
+            }
+            let provenance = if missing <= filling.linear_hours {
+                Provenance::Linear
+            } else if missing <= filling.diurnal_hours && element != Element::WindDirection {
+                Provenance::Diurnal
+            } else {
+                continue;
+            };
+            let cycle = match provenance {
+                Provenance::Diurnal => {
+                    let cycle = diurnal_cycle(station, element, h0, h1, filling.diurnal_days);
+                    if cycle.iter().any(Option::is_none) {
+                        continue;
+                    }
+                    Some(cycle.map(|c| c.unwrap_or_default()))
+                }
+                _ => None,
+            };
+            let span = (h1 - h0).num_minutes() as f64;
+            for observation in &mut station[i0 + 1..i1] {
+                if observation.get(element).is_some() {
+                    continue;
+                }
+                let fraction = (observation.hour - h0).num_minutes() as f64 / span;
+                let value = match cycle {
+                    // the anomalies from the cycle at both ends are interpolated linearly
+                    Some(cycle) => {
+                        let cycle_at = |hour: NaiveDateTime| cycle[hour.hour() as usize];
+                        cycle_at(observation.hour)
+                            + interpolate(element, v0 - cycle_at(h0), v1 - cycle_at(h1), fraction)
+                    }
+                    None => interpolate(element, v0, v1, fraction),
+                };
+                *observation.get_mut(element) = Some(HourlyValue {
+                    value,
+                    date: observation.hour,
+                    report_type: None,
+                    provenance,
+                });
+                filled += 1;
+            }
+        }
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use std::f64::consts::PI;
+
+    fn temperature(hour: i64) -> f64 {
+        10.0 + 5.0 * (2.0 * PI * hour as f64 / 24.0).sin()
+    }
+
+    #[test]
+    fn test_gaps() {
+        let start = NaiveDate::from_ymd_opt(2020, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let missing = |h: i64| (30..36).contains(&h) || (50..52).contains(&h) || h >= 56;
+        let mut observations: Vec<HourlyObservation> = (0..72)
+            .map(|h| {
+                let hour = start + Duration::hours(h);
+                let mut observation = HourlyObservation::from_reports(None, hour, &[]);
+                if !missing(h) || h == 71 {
+                    observation.temperature = Some(HourlyValue {
+                        value: temperature(h),
+                        date: hour,
+                        report_type: Some("FM-15".to_string()),
+                        provenance: Provenance::Reported,
+                    });
+                }
+                observation
+            })
+            .collect();
+
+        let gaps = find_gaps(&observations, &[Element::Temperature]);
+        assert_eq!(
+            gaps.iter().map(|g| g.hours).collect::<Vec<_>>(),
+            vec![6, 2, 15]
+        );
+        assert_eq!(gaps[0].start, start + Duration::hours(30));
+
+        let filled = fill_gaps(
+            &mut observations,
+            Element::Temperature,
+            &GapFilling::default(),
+        );
+        assert_eq!(filled, 8);
+        let linear = observations[50].temperature.as_ref().unwrap();
+        assert_eq!(linear.provenance, Provenance::Linear);
+        let expected = temperature(49) + (temperature(52) - temperature(49)) / 3.0;
+        assert!((linear.value - expected).abs() < 1e-9);
+        // the cycle is the same every day, so it is filled exactly
+        let diurnal = observations[33].temperature.as_ref().unwrap();
+        assert_eq!(diurnal.provenance, Provenance::Diurnal);
+        assert!((diurnal.value - temperature(33)).abs() < 1e-9);
+        assert!(observations[65].temperature.is_none());
+    }
+
+    #[test]
+    fn test_calm_wind_direction() {
+        let start = NaiveDate::from_ymd_opt(2020, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        // 180° at 00 and 05, calm from 01 to 03 and nothing reported at 04
+        let mut observations: Vec<HourlyObservation> = (0..6)
+            .map(|h| {
+                let hour = start + Duration::hours(h);
+                let value = |value: f64| HourlyValue {
+                    value,
+                    date: hour,
+                    report_type: Some("FM-15".to_string()),
+                    provenance: Provenance::Reported,
+                };
+                let mut observation = HourlyObservation::from_reports(None, hour, &[]);
+                match h {
+                    0 | 5 => {
+                        observation.wind_direction = Some(value(180.0));
+                        observation.wind_speed = Some(value(3.0));
+                    }
+                    1..=3 => observation.wind_speed = Some(value(0.0)),
+                    _ => {}
+                }
+                observation
+            })
+            .collect();
+
+        let gaps = find_gaps(&observations, &[Element::WindDirection]);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(
+            (gaps[0].start, gaps[0].hours),
+            (start + Duration::hours(4), 1)
+        );
+
+        let filled = fill_gaps(
+            &mut observations,
+            Element::WindDirection,
+            &GapFilling::default(),
+        );
+        assert_eq!(filled, 0);
+        assert!(observations[1..5]
+            .iter()
+            .all(|o| o.wind_direction.is_none()));
+    }
+
+    #[test]
+    fn test_interpolate_direction() {
+        assert_eq!(
+            interpolate(Element::WindDirection, 350.0, 30.0, 0.25),
+            360.0
+        );
+        assert_eq!(interpolate(Element::WindDirection, 30.0, 350.0, 0.5), 10.0);
+    }
+}
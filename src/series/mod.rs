@@ -0,0 +1,4 @@
+pub mod gaps;
+pub mod precipitation;
+pub mod resample;
+pub mod snow;
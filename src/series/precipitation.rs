@@ -0,0 +1,260 @@
+use crate::model::Record;
+use crate::series::resample::nearest_hour;
+use crate::station::id::StationId;
+use chrono::{Duration, NaiveDateTime};
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+
+/// How far in mm accumulations may disagree with the hours they cover, which allows for
+/// reports rounded to 0.01 inch.
+const TOLERANCE: f64 = 0.2;
+
+/// How an hourly precipitation amount was found.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrecipitationQuality {
+    /// No report covers the hour.
+    Missing,
+    /// From a 1 hour accumulation.
+    Measured,
+    /// The only hour of a longer accumulation not covered by shorter ones, so the remainder
+    /// of the accumulation.
+    Derived,
+    /// The remainder of a longer accumulation spread evenly over several hours.
+    Distributed,
+    /// The accumulations covering the hour disagree, e.g. a 3 hour total below the sum of
+    /// its 1 hour totals.
+    Inconsistent,
+}
+
+/// The precipitation of one station hour, the hour ending at `hour`.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HourlyPrecipitation {
+    pub station: Option<StationId>,
+    pub hour: NaiveDateTime,
+    /// Depth in mm.
+    pub amount: Option<f64>,
+    /// Whether only a trace was reported.
+    pub trace: bool,
+    /// The period in hours of the accumulation the amount comes from.
+    pub period: Option<i8>,
+    pub quality: PrecipitationQuality,
+}
+
+/// Depths and trace flags by period and end hour.
+type Accumulations = BTreeMap<(i8, NaiveDateTime), (f64, bool)>;
+
+/// Reconciles the `AAX` accumulations of the records into hourly precipitation, ordered by
+/// station and hour. Every hour covered by an accumulation is included.
+///
+/// Reports with the same period and end hour are counted once, with the largest depth.
+/// Accumulations are then applied from the shortest period to the longest, each one
+/// assigning what is left of its depth after the shorter ones to the hours that don't have
+/// an amount yet. The `SOD` and `SOM` summaries are skipped since their days are local.
+pub fn hourly_precipitation<'a, I>(records: I) -> Vec<HourlyPrecipitation>
+where
+    I: IntoIterator<Item = &'a Record>,
+{
+    let mut stations: BTreeMap<Option<StationId>, Accumulations> = BTreeMap::new();
+    for record in records {
+        if matches!(record.report_type(), Some("SOD" | "SOM")) {
+            continue;
+        }
+        for aa in record.aax() {
+            if let (Some(period), Some(depth)) = (aa.period_quantity(), aa.depth_dimension()) {
+                if period <= 0 {
+                    continue;
+                }
+                let entry = stations
+                    .entry(record.station().copied())
+                    .or_default()
+                    .entry((period, nearest_hour(record.date())))
+                    .or_insert((depth, false));
+                entry.0 = entry.0.max(depth);
+                entry.1 |= aa.condition_code() == "2";
+            }
+        }
+    }
+
+    let mut series = Vec::new();
+    for (station, accumulations) in stations {
+        let mut hours: BTreeMap<NaiveDateTime, HourlyPrecipitation> = BTreeMap::new();
+        let covered = |period: i8, end: NaiveDateTime| {
+            (0..period as i64).map(move |i| end - Duration::hours(i))
+        };
+        for (period, end) in accumulations.keys() {
+            for hour in covered(*period, *end) {
+                hours.insert(
+                    hour,
+                    HourlyPrecipitation {
+                        station,
+                        hour,
+                        amount: None,
+                        trace: false,
+                        period: None,
+                        quality: PrecipitationQuality::Missing,
+                    },
+                );
+            }
+        }
+        // the gaps between accumulations are missing hours too
+        if let (Some(first), Some(last)) = (hours.keys().next(), hours.keys().next_back()) {
+            let (mut hour, last) = (*first, *last);
+            while hour < last {
+                hours.entry(hour).or_insert(HourlyPrecipitation {
+                    station,
+                    hour,
+                    amount: None,
+                    trace: false,
+                    period: None,
+                    quality: PrecipitationQuality::Missing,
+                });
+                hour += Duration::hours(1);
+            }
+        }
+
+        for ((period, end), (depth, trace)) in accumulations {
+            let known: f64 = covered(period, end)
+                .filter_map(|hour| hours[&hour].amount)
+                .sum();
+            let unknown: Vec<NaiveDateTime> = covered(period, end)
+                .filter(|hour| hours[hour].amount.is_none())
+                .collect();
+            let remainder = depth - known;
+            if unknown.is_empty() {
+                if remainder.abs() > TOLERANCE {
+                    for hour in covered(period, end) {
+                        hours.entry(hour).and_modify(|h| {
+                            h.quality = PrecipitationQuality::Inconsistent;
+                        });
+                    }
+                }
+                continue;
+            }
+            let quality = if remainder < -TOLERANCE {
+                PrecipitationQuality::Inconsistent
+            } else if period == 1 {
+                PrecipitationQuality::Measured
+            } else if unknown.len() == 1 {
+                PrecipitationQuality::Derived
+            } else {
+                PrecipitationQuality::Distributed
+            };
+            let amount = remainder.max(0.0) / unknown.len() as f64;
+            for hour in unknown {
+                hours.entry(hour).and_modify(|h| {
+                    h.amount = Some(amount);
+                    h.trace = trace && amount == 0.0;
+                    h.period = Some(period);
+                    h.quality = quality;
+                });
+            }
+        }
+        series.extend(hours.into_values());
+    }
+    series
+}
+
+/// The total of the accumulations reported during a day.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct DailyTotal {
+    /// Total depth.
+    pub total: f64,
+    /// Whether only a trace was reported.
+    pub trace: bool,
+    /// The accumulation period of the reports the total is made of, in hours.
+    pub period: i8,
+    /// The hours covered by the reports, at most 24.
+    pub hours: i32,
+}
+
+/// Totals the `(period, end, depth, trace)` accumulations reported during a day.
+///
+/// Reports with the same period and end hour are counted once, with the largest depth, and
+/// a report whose period overlaps an earlier one of the same period is dropped, so that the
+/// same precipitation isn't counted twice. For each period the reports left are summed, and
+/// the period whose reports cover the most of the day is used, preferring the longer period
+/// on a tie.
+pub fn daily_total<I>(accumulations: I) -> Option<DailyTotal>
+where
+    I: IntoIterator<Item = (i8, NaiveDateTime, f64, bool)>,
+{
+    let mut periods: BTreeMap<i8, BTreeMap<NaiveDateTime, (f64, bool)>> = BTreeMap::new();
+    for (period, end, depth, trace) in accumulations {
+        if period <= 0 {
+            continue;
+        }
+        let entry = periods
+            .entry(period)
+            .or_default()
+            .entry(nearest_hour(end))
+            .or_insert((depth, false));
+        entry.0 = entry.0.max(depth);
+        entry.1 |= trace;
+    }
+    periods
+        .into_iter()
+        .map(|(period, reports)| {
+            let mut last: Option<NaiveDateTime> = None;
+            let mut counted = Vec::new();
+            for (end, report) in reports {
+                if last.is_none_or(|last| end - last >= Duration::hours(period as i64)) {
+                    last = Some(end);
+                    counted.push(report);
+                }
+            }
+            let total: f64 = counted.iter().map(|(depth, _)| depth).sum();
+            DailyTotal {
+                total,
+                trace: total == 0.0 && counted.iter().any(|(_, trace)| *trace),
+                period,
+                hours: (period as i32 * counted.len() as i32).min(24),
+            }
+        })
+        .max_by_key(|t| (t.hours, t.period))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::record;
+
+    #[test]
+    fn test_hourly_precipitation() {
+        let records: Vec<Record> = [
+            ("00:51", "01,0010,9,1"),
+            ("01:51", "01,0005,9,1"),
+            ("02:51", "03,0030,9,1"),
+            ("03:00", "03,0030,9,1"),
+            // 04 to 06 are only covered by the 6 hour total
+            ("05:51", "06,0090,9,1"),
+            ("06:51", "01,0030,9,1"),
+            ("07:51", "01,0000,9,1"),
+            ("08:51", "03,0010,9,1"),
+            ("09:51", "01,0000,2,1"),
+        ]
+        .iter()
+        .map(|(time, aa1)| {
+            let date = format!("2020-01-01T{}:00", time);
+            record(&[("DATE", &date), ("SLP", "99999,9"), ("AA1", aa1)])
+        })
+        .collect();
+        let hours = hourly_precipitation(&records);
+        assert_eq!(hours.len(), 10);
+        let amounts: Vec<f64> = hours.iter().map(|h| h.amount.unwrap()).collect();
+        for (amount, expected) in amounts
+            .iter()
+            .zip([1.0, 0.5, 1.5, 2.0, 2.0, 2.0, 3.0, 0.0, 0.0, 0.0])
+        {
+            assert!((amount - expected).abs() < 1e-9);
+        }
+        assert_eq!(hours[0].quality, PrecipitationQuality::Measured);
+        // the 3 hour accumulations ending at 03 are counted once
+        assert_eq!(hours[2].quality, PrecipitationQuality::Derived);
+        assert_eq!(hours[4].quality, PrecipitationQuality::Distributed);
+        // 3.0 mm at 07 but only 1.0 mm for 07 to 09
+        assert_eq!(hours[8].quality, PrecipitationQuality::Inconsistent);
+        assert!(hours[9].trace);
+    }
+}
@@ -0,0 +1,406 @@
+use crate::model::Record;
+use crate::station::id::StationId;
+use chrono::{Duration, NaiveDateTime, Timelike};
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+
+/// An element of an hourly observation.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Element {
+    /// Air temperature in °C.
+    Temperature,
+    /// Dew point temperature in °C.
+    DewPoint,
+    /// Sea level pressure in hPa.
+    SeaLevelPressure,
+    /// Station pressure in hPa, from `MA1`.
+    StationPressure,
+    /// Wind direction in degrees, `None` when calm or variable.
+    WindDirection,
+    /// Wind speed in m/s, calm observations counting as 0.
+    WindSpeed,
+    /// Visibility in meters.
+    Visibility,
+    /// Ceiling height in meters.
+    CeilingHeight,
+    /// Global horizontal irradiance in W/m², from `GM1`, else modeled in `GP1`, else the
+    /// `GH1` average.
+    GlobalIrradiance,
+    /// Direct normal irradiance in W/m², from `GM1`, else modeled in `GP1`.
+    DirectIrradiance,
+}
+
+impl Element {
+    pub const ALL: [Element; 10] = [
+        Element::Temperature,
+        Element::DewPoint,
+        Element::SeaLevelPressure,
+        Element::StationPressure,
+        Element::WindDirection,
+        Element::WindSpeed,
+        Element::Visibility,
+        Element::CeilingHeight,
+        Element::GlobalIrradiance,
+        Element::DirectIrradiance,
+    ];
+
+    /// The value of the element as reported in a record.
+    pub fn from_record(self, record: &Record) -> Option<f64> {
+        match self {
+            Element::Temperature => record.tmp().air_temperature(),
+            Element::DewPoint => record.dew().dew_point_temperature(),
+            Element::SeaLevelPressure => record.slp().pressure(),
+            Element::StationPressure => record.ma1().and_then(|ma1| ma1.station_pressure()),
+            Element::WindDirection => {
+                if record.wnd().is_calm() || record.wnd().is_variable() {
+                    None
+                } else {
+                    record.wnd().direction_angle().map(f64::from)
+                }
+            }
+            Element::WindSpeed => {
+                if record.wnd().is_calm() {
+                    Some(0.0)
+                } else {
+                    record.wnd().speed_rate()
+                }
+            }
+            Element::Visibility => record.vis().distance().map(f64::from),
+            Element::CeilingHeight => record.cig().height().map(f64::from),
+            Element::GlobalIrradiance => record
+                .gm1()
+                .and_then(|gm1| gm1.global_irradiance())
+                .or_else(|| record.gp1().and_then(|gp1| gp1.mod_global_horizontal()))
+                .or_else(|| record.gh1().and_then(|gh1| gh1.avg_solar_radiation())),
+            Element::DirectIrradiance => record
+                .gm1()
+                .and_then(|gm1| gm1.direct_beam_irradiance())
+                .or_else(|| record.gp1().and_then(|gp1| gp1.mod_direct_normal())),
+        }
+    }
+}
+
+/// Where an hourly value comes from.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Provenance {
+    /// Taken from a report.
+    #[default]
+    Reported,
+    /// Interpolated linearly across a short gap, see `series::gaps`.
+    Linear,
+    /// Interpolated along the mean diurnal cycle across a longer gap.
+    Diurnal,
+}
+
+/// The value of an element and the report it was taken from. Filled values have the date
+/// of their hour and no report type.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HourlyValue {
+    pub value: f64,
+    pub date: NaiveDateTime,
+    pub report_type: Option<String>,
+    pub provenance: Provenance,
+}
+
+/// Whether the record reports a wind, counting calm and variable winds.
+fn has_wind(record: &Record) -> bool {
+    record.wnd().is_calm()
+        || record.wnd().is_variable()
+        || record.wnd().direction_angle().is_some()
+        || record.wnd().speed_rate().is_some()
+}
+
+/// One observation of a station for an hour.
+///
+/// The elements come from the report chosen for the hour, or from the next best report of
+/// the hour when the chosen one lacks them. The wind direction and speed are taken together
+/// from the first report with a wind, so a calm report is not given the direction of
+/// another. Hours without reports have no elements.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HourlyObservation {
+    pub station: Option<StationId>,
+    /// The top of the hour.
+    pub hour: NaiveDateTime,
+    /// The date, report type and source of the chosen report.
+    pub date: Option<NaiveDateTime>,
+    pub report_type: Option<String>,
+    pub source: Option<String>,
+    /// The number of reports within the window of the hour.
+    pub reports: usize,
+    pub temperature: Option<HourlyValue>,
+    pub dew_point: Option<HourlyValue>,
+    pub sea_level_pressure: Option<HourlyValue>,
+    pub station_pressure: Option<HourlyValue>,
+    pub wind_direction: Option<HourlyValue>,
+    pub wind_speed: Option<HourlyValue>,
+    pub visibility: Option<HourlyValue>,
+    pub ceiling_height: Option<HourlyValue>,
+    pub global_irradiance: Option<HourlyValue>,
+    pub direct_irradiance: Option<HourlyValue>,
+}
+
+impl HourlyObservation {
+    /// Builds the observation from the reports of the hour, most preferred first.
+    pub fn from_reports(
+        station: Option<StationId>,
+        hour: NaiveDateTime,
+        reports: &[&Record],
+    ) -> HourlyObservation {
+        let mut observation = HourlyObservation {
+            station,
+            hour,
+            date: reports.first().map(|r| r.date()),
+            report_type: reports
+                .first()
+                .and_then(|r| r.report_type())
+                .map(String::from),
+            source: reports.first().and_then(|r| r.source()).map(String::from),
+            reports: reports.len(),
+            temperature: None,
+            dew_point: None,
+            sea_level_pressure: None,
+            station_pressure: None,
+            wind_direction: None,
+            wind_speed: None,
+            visibility: None,
+            ceiling_height: None,
+            global_irradiance: None,
+            direct_irradiance: None,
+        };
+        let wind = reports.iter().find(|r| has_wind(r));
+        for element in Element::ALL {
+            let value = |r: &&Record| {
+                element.from_record(r).map(|value| HourlyValue {
+                    value,
+                    date: r.date(),
+                    report_type: r.report_type().map(String::from),
+                    provenance: Provenance::Reported,
+                })
+            };
+            *observation.get_mut(element) = match element {
+                Element::WindDirection | Element::WindSpeed => wind.and_then(value),
+                _ => reports.iter().find_map(value),
+            };
+        }
+        observation
+    }
+
+    pub fn get(&self, element: Element) -> Option<&HourlyValue> {
+        match element {
+            Element::Temperature => self.temperature.as_ref(),
+            Element::DewPoint => self.dew_point.as_ref(),
+            Element::SeaLevelPressure => self.sea_level_pressure.as_ref(),
+            Element::StationPressure => self.station_pressure.as_ref(),
+            Element::WindDirection => self.wind_direction.as_ref(),
+            Element::WindSpeed => self.wind_speed.as_ref(),
+            Element::Visibility => self.visibility.as_ref(),
+            Element::CeilingHeight => self.ceiling_height.as_ref(),
+            Element::GlobalIrradiance => self.global_irradiance.as_ref(),
+            Element::DirectIrradiance => self.direct_irradiance.as_ref(),
+        }
+    }
+
+    pub fn get_mut(&mut self, element: Element) -> &mut Option<HourlyValue> {
+        match element {
+            Element::Temperature => &mut self.temperature,
+            Element::DewPoint => &mut self.dew_point,
+            Element::SeaLevelPressure => &mut self.sea_level_pressure,
+            Element::StationPressure => &mut self.station_pressure,
+            Element::WindDirection => &mut self.wind_direction,
+            Element::WindSpeed => &mut self.wind_speed,
+            Element::Visibility => &mut self.visibility,
+            Element::CeilingHeight => &mut self.ceiling_height,
+            Element::GlobalIrradiance => &mut self.global_irradiance,
+            Element::DirectIrradiance => &mut self.direct_irradiance,
+        }
+    }
+
+    pub fn value(&self, element: Element) -> Option<f64> {
+        self.get(element).map(|v| v.value)
+    }
+}
+
+/// How the report of an hour is chosen. Reports are ranked by report type, then source,
+/// then distance from the top of the hour; types and sources that are not listed rank
+/// after the listed ones.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ResamplePriority {
+    /// Report types, most preferred first.
+    pub report_types: Vec<String>,
+    /// Data sources, most preferred first.
+    pub sources: Vec<String>,
+    /// Report types that are never used.
+    pub excluded_report_types: Vec<String>,
+    /// How far from the top of the hour a report may be, in minutes. Each report belongs to
+    /// the nearest hour, so windows above 30 minutes make no difference.
+    pub window: i64,
+}
+
+impl Default for ResamplePriority {
+    /// METAR, then SPECI, then SAO and SYNOP reports, preferring merged ASOS/AWOS and USAF
+    /// data. The SOD and SOM daily and monthly summaries are excluded.
+    fn default() -> ResamplePriority {
+        let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        ResamplePriority {
+            report_types: strings(&[
+                "FM-15", "FM-16", "SAO", "SY-MT", "FM-12", "SY-SA", "AUTO", "SA-AU",
+            ]),
+            sources: strings(&["7", "6", "4", "3", "1", "2"]),
+            excluded_report_types: strings(&["SOD", "SOM"]),
+            window: 30,
+        }
+    }
+}
+
+impl ResamplePriority {
+    fn rank(&self, record: &Record, hour: NaiveDateTime) -> (usize, usize, i64, NaiveDateTime) {
+        let position = |values: &[String], value: Option<&str>| {
+            value
+                .and_then(|value| values.iter().position(|v| v == value))
+                .unwrap_or(values.len())
+        };
+        (
+            position(&self.report_types, record.report_type()),
+            position(&self.sources, record.source()),
+            (record.date() - hour).num_minutes().abs(),
+            record.date(),
+        )
+    }
+
+    fn is_excluded(&self, record: &Record) -> bool {
+        record
+            .report_type()
+            .is_some_and(|t| self.excluded_report_types.iter().any(|e| e == t))
+    }
+}
+
+/// The top of the hour nearest to `date`.
+pub fn nearest_hour(date: NaiveDateTime) -> NaiveDateTime {
+    let shifted = date + Duration::minutes(30);
+    shifted
+        .date()
+        .and_hms_opt(shifted.hour(), 0, 0)
+        .unwrap_or(shifted)
+}
+
+/// Resamples records to one observation per station and hour, ordered by station and hour.
+/// Every hour from the first to the last report of a station is included, so hours without
+/// reports appear as empty observations.
+pub fn resample<'a, I>(records: I, priority: &ResamplePriority) -> Vec<HourlyObservation>
+where
+    I: IntoIterator<Item = &'a Record>,
+{
+    let mut stations: BTreeMap<Option<StationId>, BTreeMap<NaiveDateTime, Vec<&Record>>> =
+        BTreeMap::new();
+    for record in records {
+        if priority.is_excluded(record) {
+            continue;
+        }
+        let hour = nearest_hour(record.date());
+        if (record.date() - hour).num_minutes().abs() > priority.window {
+            continue;
+        }
+        stations
+            .entry(record.station().copied())
+            .or_default()
+            .entry(hour)
+            .or_default()
+            .push(record);
+    }
+
+    let mut observations = Vec::new();
+    for (station, mut hours) in stations {
+        let (first, last) = match (hours.keys().next(), hours.keys().next_back()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => continue,
+        };
+        let mut hour = first;
+        while hour <= last {
+            let mut reports = hours.remove(&hour).unwrap_or_default();
+            reports.sort_by_key(|r| priority.rank(r, hour));
+            observations.push(HourlyObservation::from_reports(station, hour, &reports));
+            hour += Duration::hours(1);
+        }
+    }
+    observations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::records;
+
+    #[test]
+    fn test_resample() {
+        let records = records(&[
+            &[("DATE", "2020-01-01T05:51:00"), ("SLP", "99999,9")],
+            &[
+                ("DATE", "2020-01-01T06:00:00"),
+                ("SOURCE", "4"),
+                ("REPORT_TYPE", "FM-12"),
+                ("WND", "260,1,N,0050,1"),
+                ("CIG", "99999,9,9,N"),
+                ("VIS", "999999,9,9,9"),
+                ("TMP", "+0060,1"),
+                ("DEW", "-0040,1"),
+            ],
+            &[
+                ("DATE", "2020-01-01T06:10:00"),
+                ("REPORT_TYPE", "FM-16"),
+                ("CIG", "00300,1,9,N"),
+                ("VIS", "004000,1,9,9"),
+                ("TMP", "+0050,1"),
+                ("DEW", "-0040,1"),
+                ("SLP", "99999,9"),
+            ],
+            &[
+                ("DATE", "2020-01-01T08:51:00"),
+                ("WND", "999,9,C,0000,1"),
+                ("TMP", "+0011,1"),
+                ("SLP", "10120,1"),
+            ],
+            &[("DATE", "2020-01-01T23:59:00"), ("REPORT_TYPE", "SOD")],
+        ]);
+        let hours = resample(&records, &ResamplePriority::default());
+        // 06 to 09: the reports around 06, none at 07 and 08 and the 08:51 METAR at 09,
+        // while the SOD summary is excluded
+        assert_eq!(hours.len(), 4);
+        let six = &hours[0];
+        assert_eq!(six.hour.to_string(), "2020-01-01 06:00:00");
+        assert_eq!(six.report_type.as_deref(), Some("FM-15"));
+        assert_eq!(six.reports, 3);
+        assert_eq!(six.value(Element::Temperature), Some(6.1));
+        // the METAR has no sea level pressure, so it comes from the SYNOP
+        let slp = six.sea_level_pressure.as_ref().unwrap();
+        assert_eq!(slp.value, 1010.0);
+        assert_eq!(slp.report_type.as_deref(), Some("FM-12"));
+        assert_eq!(hours[1].reports, 0);
+        assert!(hours[1].temperature.is_none());
+        let nine = &hours[3];
+        assert_eq!(nine.value(Element::WindSpeed), Some(0.0));
+        assert_eq!(nine.value(Element::WindDirection), None);
+    }
+
+    #[test]
+    fn test_calm_wind() {
+        let records = records(&[
+            &[("DATE", "2020-01-01T05:51:00"), ("WND", "999,9,C,0000,1")],
+            &[
+                ("DATE", "2020-01-01T06:00:00"),
+                ("SOURCE", "4"),
+                ("REPORT_TYPE", "FM-12"),
+                ("WND", "260,1,N,0050,1"),
+            ],
+        ]);
+        let hours = resample(&records, &ResamplePriority::default());
+        assert_eq!(hours.len(), 1);
+        // the SYNOP direction doesn't belong to the calm METAR wind
+        let speed = hours[0].wind_speed.as_ref().unwrap();
+        assert_eq!(speed.value, 0.0);
+        assert_eq!(speed.report_type.as_deref(), Some("FM-15"));
+        assert!(hours[0].wind_direction.is_none());
+    }
+}
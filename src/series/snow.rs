@@ -0,0 +1,262 @@
+use crate::model::Record;
+use crate::series::precipitation::daily_total;
+use crate::station::id::StationId;
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+
+/// How far in cm snow values may disagree before they are flagged.
+const TOLERANCE: f64 = 1.0;
+
+/// Where the snowfall of a day comes from.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnowfallSource {
+    /// The `ALX` snow accumulations.
+    Accumulation,
+    /// The `AN1` snow accumulation for the day of the month.
+    DayOfMonth,
+    /// The increase in snow depth since the day before, when nothing else was reported.
+    DepthChange,
+}
+
+/// Snow values of a day that contradict each other.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnowFlag {
+    /// More water equivalent than snow depth.
+    WaterEquivalentAboveDepth,
+    /// The snow depth rose by more than the reported snowfall.
+    DepthIncreaseAboveSnowfall,
+    /// More snowfall than the greatest 24 hour snowfall reported in `AM1` for the month.
+    SnowfallAboveGreatest24Hour,
+}
+
+/// The snow of one station day.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DailySnow {
+    pub station: Option<StationId>,
+    pub date: NaiveDate,
+    /// The last reported snow depth of the day in cm, from `AJ1`.
+    pub depth: Option<i16>,
+    /// The last reported water equivalent of the snow in mm, from `AJ1`.
+    pub water_equivalent: Option<f64>,
+    /// New snowfall in cm.
+    pub snowfall: Option<f64>,
+    pub snowfall_source: Option<SnowfallSource>,
+    /// The greatest 24 hour snowfall of the month in cm, from an `AM1` reported this day.
+    pub greatest_24_hour_snowfall: Option<f64>,
+    /// The greatest snow depth of the month in cm, from an `AK1` reported this day.
+    pub greatest_depth: Option<i16>,
+    pub flags: Vec<SnowFlag>,
+}
+
+/// The snowfall of a day from the `ALX` accumulations, totalled like precipitation by
+/// `series::precipitation::daily_total`.
+fn accumulated_snowfall(records: &[&Record]) -> Option<f64> {
+    let accumulations = records.iter().flat_map(|record| {
+        record.alx().filter_map(|al| {
+            let period = al.period_quantity()?;
+            let depth = al.depth_dimension()?;
+            Some((period, record.date(), f64::from(depth), false))
+        })
+    });
+    daily_total(accumulations).map(|t| t.total)
+}
+
+/// The snow of each station day with snow reports, ordered by station and date.
+///
+/// Snowfall comes from the `ALX` accumulations, else from the `AN1` accumulation for the day
+/// of the month, else from the rise in snow depth since the day before. An `AN1` period of
+/// more than 24 hours is a total since the start of the month, so the day's snowfall is the
+/// difference from the previous day's total.
+pub fn daily_snow<'a, I>(records: I) -> Vec<DailySnow>
+where
+    I: IntoIterator<Item = &'a Record>,
+{
+    let mut days: BTreeMap<(Option<StationId>, NaiveDate), Vec<&Record>> = BTreeMap::new();
+    for record in records {
+        let has_snow = record.aj1().is_some()
+            || record.ak1().is_some()
+            || record.alx().next().is_some()
+            || record.am1().is_some()
+            || record.an1().is_some();
+        if has_snow {
+            days.entry((record.station().copied(), record.date().date()))
+                .or_default()
+                .push(record);
+        }
+    }
+
+    let mut series: Vec<DailySnow> = Vec::new();
+    // the last AN1 period and total of the previous day
+    let mut previous_an1: Option<(Option<StationId>, NaiveDate, i16, f64)> = None;
+    for ((station, date), mut records) in days {
+        records.sort_by_key(|r| r.date());
+        let depth = records
+            .iter()
+            .rev()
+            .find_map(|r| r.aj1().and_then(|aj1| aj1.depth_dimension()));
+        let water_equivalent = records.iter().rev().find_map(|r| {
+            r.aj1()
+                .and_then(|aj1| aj1.equivalent_water_depth_dimension())
+        });
+
+        let an1 = records.iter().rev().find_map(|r| {
+            r.an1()
+                .and_then(|an1| an1.period_quantity().zip(an1.depth_dimension()))
+        });
+        let day_of_month = an1.and_then(|(period, total)| {
+            if period <= 24 {
+                return Some(total);
+            }
+            match previous_an1 {
+                Some((s, d, p, previous))
+                    if s == station && d.succ_opt() == Some(date) && p == period - 24 =>
+                {
+                    Some((total - previous).max(0.0))
+                }
+                _ => None,
+            }
+        });
+        previous_an1 = an1.map(|(period, total)| (station, date, period, total));
+
+        let previous_depth = series
+            .last()
+            .filter(|p| p.station == station && p.date.succ_opt() == Some(date))
+            .and_then(|p| p.depth);
+        let depth_change = depth
+            .zip(previous_depth)
+            .map(|(depth, previous)| f64::from((depth - previous).max(0)));
+
+        let (snowfall, snowfall_source) = if let Some(snowfall) = accumulated_snowfall(&records) {
+            (Some(snowfall), Some(SnowfallSource::Accumulation))
+        } else if let Some(snowfall) = day_of_month {
+            (Some(snowfall), Some(SnowfallSource::DayOfMonth))
+        } else if let Some(snowfall) = depth_change {
+            (Some(snowfall), Some(SnowfallSource::DepthChange))
+        } else {
+            (None, None)
+        };
+
+        let mut flags = Vec::new();
+        if let (Some(depth), Some(water_equivalent)) = (depth, water_equivalent) {
+            // the depth is in cm and the water equivalent in mm
+            if water_equivalent > f64::from(depth) * 10.0 + TOLERANCE {
+                flags.push(SnowFlag::WaterEquivalentAboveDepth);
+            }
+        }
+        if let (Some(change), Some(snowfall)) = (depth_change, snowfall) {
+            if snowfall_source != Some(SnowfallSource::DepthChange) && change > snowfall + TOLERANCE
+            {
+                flags.push(SnowFlag::DepthIncreaseAboveSnowfall);
+            }
+        }
+
+        series.push(DailySnow {
+            station,
+            date,
+            depth,
+            water_equivalent,
+            snowfall,
+            snowfall_source,
+            greatest_24_hour_snowfall: records
+                .iter()
+                .rev()
+                .find_map(|r| r.am1().and_then(|am1| am1.depth_dimension())),
+            greatest_depth: records
+                .iter()
+                .rev()
+                .find_map(|r| r.ak1().and_then(|ak1| ak1.depth_dimension())),
+            flags,
+        });
+    }
+
+    // the AM1 greatest 24 hour snowfall applies to every day of its month
+    let mut greatest: BTreeMap<(Option<StationId>, i32, u32), f64> = BTreeMap::new();
+    for day in &series {
+        if let Some(snowfall) = day.greatest_24_hour_snowfall {
+            greatest.insert((day.station, day.date.year(), day.date.month()), snowfall);
+        }
+    }
+    for day in &mut series {
+        let month_greatest = greatest.get(&(day.station, day.date.year(), day.date.month()));
+        if let (Some(greatest), Some(snowfall)) = (month_greatest, day.snowfall) {
+            if snowfall > greatest + TOLERANCE {
+                day.flags.push(SnowFlag::SnowfallAboveGreatest24Hour);
+            }
+        }
+    }
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::{record, records};
+
+    #[test]
+    fn test_daily_snow() {
+        let records: Vec<Record> = [
+            ("01T12:00", "0010,1,1,000200,1,1", "", "", ""),
+            ("02T12:00", "0015,1,1,999999,9,9", "", "", ""),
+            ("03T12:00", "0030,1,1,004000,1,1", "24,010,1,1", "", ""),
+            ("04T12:00", "", "", "", "024,0050,1,1"),
+            ("31T23:59", "", "", "0080,1,0203,9999,9999,1", ""),
+        ]
+        .iter()
+        .map(|(date, aj1, al1, am1, an1)| {
+            let date = format!("2020-01-{}:00", date);
+            record(&[
+                ("DATE", &date),
+                ("REPORT_TYPE", "SOD"),
+                ("AJ1", aj1),
+                ("AL1", al1),
+                ("AM1", am1),
+                ("AN1", an1),
+            ])
+        })
+        .collect();
+        let days = daily_snow(&records);
+        assert_eq!(days.len(), 5);
+
+        assert_eq!(days[0].depth, Some(10));
+        assert_eq!(days[0].water_equivalent, Some(20.0));
+        assert_eq!(days[0].snowfall, None);
+        assert_eq!(days[1].snowfall, Some(5.0));
+        assert_eq!(days[1].snowfall_source, Some(SnowfallSource::DepthChange));
+        assert_eq!(days[2].snowfall, Some(10.0));
+        assert_eq!(days[2].snowfall_source, Some(SnowfallSource::Accumulation));
+        assert_eq!(
+            days[2].flags,
+            vec![
+                SnowFlag::WaterEquivalentAboveDepth,
+                SnowFlag::DepthIncreaseAboveSnowfall,
+                SnowFlag::SnowfallAboveGreatest24Hour
+            ]
+        );
+        assert_eq!(days[3].snowfall, Some(5.0));
+        assert_eq!(days[3].snowfall_source, Some(SnowfallSource::DayOfMonth));
+        assert!(days[3].flags.is_empty());
+        assert_eq!(days[4].greatest_24_hour_snowfall, Some(8.0));
+    }
+
+    #[test]
+    fn test_accumulated_snowfall() {
+        let records = records(&[
+            // the METAR and SYNOP 6 hour totals ending at 06 are the same report
+            &[("DATE", "2020-01-01T05:51:00"), ("AL1", "06,004,1,1")],
+            &[
+                ("DATE", "2020-01-01T06:00:00"),
+                ("SOURCE", "4"),
+                ("REPORT_TYPE", "FM-12"),
+                ("AL1", "06,004,1,1"),
+            ],
+            // overlaps the 6 hours ending at 06
+            &[("DATE", "2020-01-01T08:51:00"), ("AL1", "06,006,1,1")],
+            &[("DATE", "2020-01-01T11:51:00"), ("AL1", "06,002,1,1")],
+        ]);
+        let reports: Vec<&Record> = records.iter().collect();
+        assert_eq!(accumulated_snowfall(&reports), Some(6.0));
+    }
+}
@@ -0,0 +1,240 @@
+use crate::model::Record;
+use crate::station::id::StationId;
+use crate::util::naive_date_from_str;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// A row of NCEI's `isd-history.csv`, as published at
+/// <https://www.ncei.noaa.gov/pub/data/noaa/isd-history.csv>.
+#[derive(Deserialize, Debug)]
+struct HistoryRow {
+    #[serde(rename = "USAF")]
+    usaf: String,
+    #[serde(rename = "WBAN")]
+    wban: String,
+    #[serde(rename = "STATION NAME")]
+    name: Option<String>,
+    #[serde(rename = "CTRY")]
+    country: Option<String>,
+    #[serde(rename = "STATE")]
+    state: Option<String>,
+    #[serde(rename = "ICAO")]
+    icao: Option<String>,
+    #[serde(rename = "LAT")]
+    latitude: Option<f64>,
+    #[serde(rename = "LON")]
+    longitude: Option<f64>,
+    #[serde(rename = "ELEV(M)")]
+    elevation: Option<f64>,
+    #[serde(rename = "BEGIN", deserialize_with = "naive_date_from_str")]
+    begin: NaiveDate,
+    #[serde(rename = "END", deserialize_with = "naive_date_from_str")]
+    end: NaiveDate,
+}
+
+/// The metadata and period of record of a single station.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct StationHistory {
+    id: StationId,
+    name: Option<String>,
+    /// FIPS country code.
+    country: Option<String>,
+    /// State, for US stations.
+    state: Option<String>,
+    icao: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    /// Elevation in meters.
+    elevation: Option<f64>,
+    /// The first date with data in the archive.
+    begin: NaiveDate,
+    /// The last date with data in the archive.
+    end: NaiveDate,
+}
+
+impl StationHistory {
+    pub fn id(&self) -> &StationId {
+        &self.id
+    }
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_deref()
+    }
+    pub fn state(&self) -> Option<&str> {
+        self.state.as_deref()
+    }
+    pub fn icao(&self) -> Option<&str> {
+        self.icao.as_deref()
+    }
+    pub fn latitude(&self) -> Option<f64> {
+        self.latitude
+    }
+    pub fn longitude(&self) -> Option<f64> {
+        self.longitude
+    }
+    pub fn elevation(&self) -> Option<f64> {
+        self.elevation
+    }
+    pub fn begin(&self) -> NaiveDate {
+        self.begin
+    }
+    pub fn end(&self) -> NaiveDate {
+        self.end
+    }
+
+    /// Whether the period of record overlaps the inclusive range `start..=end`.
+    pub fn is_active_between(&self, start: NaiveDate, end: NaiveDate) -> bool {
+        self.begin <= end && self.end >= start
+    }
+}
+
+impl TryFrom<HistoryRow> for StationHistory {
+    type Error = &'static str;
+
+    fn try_from(row: HistoryRow) -> Result<Self, Self::Error> {
+        // isd-history uses 0,0 and the -999 family for unknown positions, while a single 0 is
+        // a station on the equator or the prime meridian
+        let coordinate = |v: Option<f64>| v.filter(|v| *v > -999.0);
+        let (latitude, longitude) = match (coordinate(row.latitude), coordinate(row.longitude)) {
+            (Some(lat), Some(lon)) if lat != 0.0 || lon != 0.0 => (Some(lat), Some(lon)),
+            _ => (None, None),
+        };
+        Ok(StationHistory {
+            id: StationId::new(&row.usaf, &row.wban)?,
+            name: row.name,
+            country: row.country,
+            state: row.state,
+            icao: row.icao,
+            latitude,
+            longitude,
+            elevation: row.elevation.filter(|v| *v > -999.0),
+            begin: row.begin,
+            end: row.end,
+        })
+    }
+}
+
+/// An in-memory lookup of `isd-history.csv` keyed by station.
+#[derive(Debug, Default)]
+pub struct StationCatalog {
+    stations: BTreeMap<StationId, StationHistory>,
+}
+
+impl StationCatalog {
+    pub fn from_reader<R: io::Read>(rdr: R) -> Result<StationCatalog, Box<dyn Error>> {
+        let mut rdr = csv::Reader::from_reader(rdr);
+        let mut catalog = StationCatalog::default();
+        for result in rdr.deserialize() {
+            let row: HistoryRow = result?;
+            catalog.insert(StationHistory::try_from(row)?);
+        }
+        Ok(catalog)
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<StationCatalog, Box<dyn Error>> {
+        StationCatalog::from_reader(File::open(path)?)
+    }
+
+    pub fn insert(&mut self, station: StationHistory) {
+        self.stations.insert(station.id, station);
+    }
+
+    pub fn get(&self, id: &StationId) -> Option<&StationHistory> {
+        self.stations.get(id)
+    }
+
+    /// The station a `Record` was observed at.
+    pub fn for_record(&self, record: &Record) -> Option<&StationHistory> {
+        record.station().and_then(|id| self.get(id))
+    }
+
+    /// Wraps a `Record` with the ICAO code, country and state of its station for serialization.
+    pub fn enrich<'a>(&'a self, record: &'a Record) -> EnrichedRecord<'a> {
+        let station = self.for_record(record);
+        EnrichedRecord {
+            record,
+            icao: station.and_then(|s| s.icao()),
+            country: station.and_then(|s| s.country()),
+            state: station.and_then(|s| s.state()),
+        }
+    }
+
+    /// Stations whose period of record overlaps the inclusive range `start..=end`.
+    pub fn active_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> impl Iterator<Item = &StationHistory> {
+        self.iter().filter(move |s| s.is_active_between(start, end))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StationHistory> {
+        self.stations.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.stations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stations.is_empty()
+    }
+}
+
+/// A `Record` serialized together with metadata from its `StationCatalog` entry.
+#[skip_serializing_none]
+#[derive(Serialize, Debug)]
+pub struct EnrichedRecord<'a> {
+    #[serde(flatten)]
+    record: &'a Record,
+    icao: Option<&'a str>,
+    country: Option<&'a str>,
+    state: Option<&'a str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StationCatalog;
+    use chrono::NaiveDate;
+
+    static HISTORY: &str = r#""USAF","WBAN","STATION NAME","CTRY","STATE","ICAO","LAT","LON","ELEV(M)","BEGIN","END"
+"007018","99999","WXPOD 7018","","","","+00.000","+000.000","+7018.0","20110309","20130730"
+"725030","14732","LA GUARDIA AIRPORT","US","NY","KLGA","+40.779","-073.880","+0003.4","19730101","20240101"
+"036830","99999","GREENWICH","UK","","","+51.478","+000.000","+0046.0","20000101","20001231"
+"#;
+
+    #[test]
+    fn test_parse_history() {
+        let catalog = StationCatalog::from_reader(HISTORY.as_bytes()).unwrap();
+        assert_eq!(catalog.len(), 3);
+
+        let lga = catalog.get(&"72503014732".parse().unwrap()).unwrap();
+        assert_eq!(lga.icao(), Some("KLGA"));
+        assert_eq!(lga.country(), Some("US"));
+        assert_eq!(lga.latitude(), Some(40.779));
+        assert_eq!(lga.begin(), NaiveDate::from_ymd_opt(1973, 1, 1).unwrap());
+
+        let pod = catalog.get(&"007018-99999".parse().unwrap()).unwrap();
+        assert_eq!(pod.icao(), None);
+        assert_eq!(pod.latitude(), None);
+        assert_eq!(pod.longitude(), None);
+
+        let greenwich = catalog.get(&"036830-99999".parse().unwrap()).unwrap();
+        assert_eq!(greenwich.longitude(), Some(0.0));
+
+        let start = NaiveDate::from_ymd_opt(2015, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2015, 12, 31).unwrap();
+        let active: Vec<_> = catalog.active_between(start, end).collect();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].name(), Some("LA GUARDIA AIRPORT"));
+    }
+}
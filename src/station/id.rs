@@ -0,0 +1,107 @@
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use std::fmt;
+use std::str::FromStr;
+
+/// The identifier of an ISD station, made of a 6 character USAF (Air Force Datsav3) catalog number
+/// and a 5 digit NCEI WBAN (Weather Bureau, Air Force and Navy) number.
+///
+/// The global-hourly CSV files carry the two parts concatenated (`72503014732`), while the ISD
+/// file names and `isd-history.csv` keep them apart (`725030-14732`).
+#[derive(DeserializeFromStr, SerializeDisplay, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StationId {
+    usaf: [u8; 6],
+    wban: [u8; 5],
+}
+
+impl StationId {
+    pub fn new(usaf: &str, wban: &str) -> Result<StationId, &'static str> {
+        let usaf = usaf.trim();
+        let wban = wban.trim();
+        if usaf.len() != 6 || !usaf.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return Err("USAF identifier must be 6 alphanumeric characters");
+        }
+        if wban.len() != 5 || !wban.bytes().all(|b| b.is_ascii_digit()) {
+            return Err("WBAN identifier must be 5 digits");
+        }
+        let mut id = StationId {
+            usaf: [0; 6],
+            wban: [0; 5],
+        };
+        id.usaf.copy_from_slice(usaf.as_bytes());
+        id.wban.copy_from_slice(wban.as_bytes());
+        Ok(id)
+    }
+
+    /// The USAF catalog number, `999999` when the station has none.
+    pub fn usaf(&self) -> &str {
+        std::str::from_utf8(&self.usaf).unwrap()
+    }
+
+    /// The WBAN number, `99999` when the station has none.
+    pub fn wban(&self) -> &str {
+        std::str::from_utf8(&self.wban).unwrap()
+    }
+
+    pub fn has_usaf(&self) -> bool {
+        self.usaf() != "999999"
+    }
+
+    pub fn has_wban(&self) -> bool {
+        self.wban() != "99999"
+    }
+
+    /// The `USAF-WBAN` form used in ISD file names, e.g. `725030-14732-2020.gz`.
+    pub fn file_stem(&self) -> String {
+        format!("{}-{}", self.usaf(), self.wban())
+    }
+}
+
+impl FromStr for StationId {
+    type Err = &'static str;
+
+    /// Accepts both the concatenated 11 character form and the hyphenated `USAF-WBAN` form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.split_once('-') {
+            Some((usaf, wban)) => StationId::new(usaf, wban),
+            None if s.len() == 11 && s.is_char_boundary(6) => StationId::new(&s[..6], &s[6..]),
+            None => Err("Station identifier must be 11 characters or USAF-WBAN"),
+        }
+    }
+}
+
+impl fmt::Display for StationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.usaf(), self.wban())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StationId;
+
+    #[test]
+    fn test_parse_station_id() {
+        let csv: StationId = "72503014732".parse().unwrap();
+        let file: StationId = "725030-14732".parse().unwrap();
+        assert_eq!(csv, file);
+        assert_eq!(csv.usaf(), "725030");
+        assert_eq!(csv.wban(), "14732");
+        assert_eq!(csv.to_string(), "72503014732");
+        assert_eq!(csv.file_stem(), "725030-14732");
+        // USAF numbers may contain letters, WBAN numbers may not
+        assert!("A0000594644".parse::<StationId>().is_ok());
+        assert!("72503014A32".parse::<StationId>().is_err());
+        assert!("7250301473".parse::<StationId>().is_err());
+        assert!("725030-1473".parse::<StationId>().is_err());
+    }
+
+    #[test]
+    fn test_station_id_order() {
+        let a: StationId = "72503014732".parse().unwrap();
+        let b: StationId = "72503094789".parse().unwrap();
+        let c: StationId = "72505004781".parse().unwrap();
+        assert!(a < b && b < c);
+        assert!(!"99999914732".parse::<StationId>().unwrap().has_usaf());
+    }
+}
@@ -0,0 +1,209 @@
+use crate::model::Record;
+use crate::station::id::StationId;
+use crate::util::{days_in_month, months_between};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// A row of NCEI's `isd-inventory.csv`: the number of observations a station has for each month of a year.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "UPPERCASE")]
+struct InventoryRow {
+    usaf: String,
+    wban: String,
+    year: i32,
+    jan: u32,
+    feb: u32,
+    mar: u32,
+    apr: u32,
+    may: u32,
+    jun: u32,
+    jul: u32,
+    aug: u32,
+    sep: u32,
+    oct: u32,
+    nov: u32,
+    dec: u32,
+}
+
+/// A month where the inventory and the parsed `Record`s disagree on the number of observations.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CountDiscrepancy {
+    pub station: StationId,
+    pub year: i32,
+    pub month: u32,
+    /// Observations listed in `isd-inventory.csv`.
+    pub inventory: u32,
+    /// Observations seen in the parsed records.
+    pub parsed: u32,
+}
+
+/// Monthly observation counts per station-year, from `isd-inventory.csv`.
+#[derive(Debug, Default)]
+pub struct Inventory {
+    counts: BTreeMap<StationId, BTreeMap<i32, [u32; 12]>>,
+}
+
+impl Inventory {
+    pub fn from_reader<R: io::Read>(rdr: R) -> Result<Inventory, Box<dyn Error>> {
+        let mut rdr = csv::Reader::from_reader(rdr);
+        let mut inventory = Inventory::default();
+        for result in rdr.deserialize() {
+            let row: InventoryRow = result?;
+            let id = StationId::new(&row.usaf, &row.wban)?;
+            inventory.counts.entry(id).or_default().insert(
+                row.year,
+                [
+                    row.jan, row.feb, row.mar, row.apr, row.may, row.jun, row.jul, row.aug,
+                    row.sep, row.oct, row.nov, row.dec,
+                ],
+            );
+        }
+        Ok(inventory)
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Inventory, Box<dyn Error>> {
+        Inventory::from_reader(File::open(path)?)
+    }
+
+    /// The observation count for a month (1-12), `None` when the station-year is not in the inventory.
+    pub fn count(&self, station: &StationId, year: i32, month: u32) -> Option<u32> {
+        let months = self.counts.get(station)?.get(&year)?;
+        months.get(month.checked_sub(1)? as usize).copied()
+    }
+
+    /// The total observations over the months spanned by `start..=end`.
+    pub fn total(&self, station: &StationId, start: NaiveDate, end: NaiveDate) -> u32 {
+        months_between(start, end)
+            .map(|(year, month)| self.count(station, year, month).unwrap_or(0))
+            .sum()
+    }
+
+    /// The fraction of expected observations present over the months spanned by `start..=end`,
+    /// with `per_day` observations expected each day (24 for hourly reporting).
+    /// Months with more reports than expected are capped so that specials cannot make up for gaps.
+    pub fn coverage(
+        &self,
+        station: &StationId,
+        start: NaiveDate,
+        end: NaiveDate,
+        per_day: u32,
+    ) -> f64 {
+        let mut expected = 0u64;
+        let mut present = 0u64;
+        for (year, month) in months_between(start, end) {
+            let month_expected = (days_in_month(year, month) * per_day) as u64;
+            expected += month_expected;
+            present += (self.count(station, year, month).unwrap_or(0) as u64).min(month_expected);
+        }
+        if expected == 0 {
+            0.0
+        } else {
+            present as f64 / expected as f64
+        }
+    }
+
+    /// Stations with at least `min_coverage` (0-1) of the expected observations over `start..=end`.
+    pub fn stations_with_coverage(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        per_day: u32,
+        min_coverage: f64,
+    ) -> Vec<StationId> {
+        self.stations()
+            .filter(|id| self.coverage(id, start, end, per_day) >= min_coverage)
+            .copied()
+            .collect()
+    }
+
+    pub fn stations(&self) -> impl Iterator<Item = &StationId> {
+        self.counts.keys()
+    }
+
+    /// Compares the inventory against the `Record`s actually parsed, returning every month of
+    /// the station-years seen in `records` where the counts differ.
+    pub fn compare<'a, I>(&self, records: I) -> Vec<CountDiscrepancy>
+    where
+        I: IntoIterator<Item = &'a Record>,
+    {
+        let mut parsed: BTreeMap<(StationId, i32, u32), u32> = BTreeMap::new();
+        let mut station_years: BTreeSet<(StationId, i32)> = BTreeSet::new();
+        for record in records {
+            if let Some(station) = record.station() {
+                let date = record.date();
+                *parsed
+                    .entry((*station, date.year(), date.month()))
+                    .or_default() += 1;
+                station_years.insert((*station, date.year()));
+            }
+        }
+
+        let mut discrepancies = Vec::new();
+        for (station, year) in station_years {
+            for month in 1..=12 {
+                let inventory = self.count(&station, year, month).unwrap_or(0);
+                let parsed = parsed.get(&(station, year, month)).copied().unwrap_or(0);
+                if inventory != parsed {
+                    discrepancies.push(CountDiscrepancy {
+                        station,
+                        year,
+                        month,
+                        inventory,
+                        parsed,
+                    });
+                }
+            }
+        }
+        discrepancies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::records;
+
+    const INVENTORY: &str = "\"USAF\",\"WBAN\",\"YEAR\",\"JAN\",\"FEB\",\"MAR\",\"APR\",\"MAY\",\"JUN\",\"JUL\",\"AUG\",\"SEP\",\"OCT\",\"NOV\",\"DEC\"
+\"725030\",\"14732\",\"2020\",\"800\",\"696\",\"0\",\"0\",\"0\",\"0\",\"0\",\"0\",\"0\",\"0\",\"0\",\"0\"
+\"722950\",\"23174\",\"2020\",\"372\",\"0\",\"0\",\"0\",\"0\",\"0\",\"0\",\"0\",\"0\",\"0\",\"0\",\"0\"";
+
+    #[test]
+    fn test_inventory() {
+        let inventory = Inventory::from_reader(INVENTORY.as_bytes()).unwrap();
+        let lga = StationId::new("725030", "14732").unwrap();
+        let lax = StationId::new("722950", "23174").unwrap();
+        assert_eq!(inventory.count(&lga, 2020, 1), Some(800));
+        assert_eq!(inventory.count(&lga, 2020, 13), None);
+        assert_eq!(inventory.count(&lga, 2019, 1), None);
+
+        let start = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2020, 2, 29).unwrap();
+        assert_eq!(inventory.total(&lga, start, end), 1496);
+        // the 800 January reports are capped at the 744 expected, so they can't make up
+        // for a short February
+        assert_eq!(inventory.coverage(&lga, start, end, 24), 1.0);
+        assert!((inventory.coverage(&lax, start, end, 24) - 372.0 / 1440.0).abs() < 1e-9);
+        assert_eq!(
+            inventory.stations_with_coverage(start, end, 24, 0.9),
+            vec![lga]
+        );
+
+        let records = records(&[
+            &[("DATE", "2020-01-01T00:51:00")],
+            &[("DATE", "2020-01-01T01:51:00")],
+        ]);
+        let discrepancies = inventory.compare(&records);
+        assert_eq!(
+            discrepancies
+                .iter()
+                .map(|d| (d.month, d.inventory, d.parsed))
+                .collect::<Vec<_>>(),
+            vec![(1, 800, 2), (2, 696, 0)]
+        );
+    }
+}
@@ -0,0 +1,5 @@
+pub mod id;
+pub mod history;
+pub mod inventory;
+pub mod nearest;
+pub mod relocation;
@@ -0,0 +1,235 @@
+use crate::model::Record;
+use crate::station::history::{StationCatalog, StationHistory};
+use crate::station::id::StationId;
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::collections::HashMap;
+
+/// Mean radius of the earth in kilometers (IUGG).
+pub const EARTH_RADIUS: f64 = 6371.0088;
+
+/// The great-circle distance in kilometers between two points given in decimal degrees.
+pub fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lon = (lon2 - lon1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// A place a station has observed from, with the period it was observed there if known.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct StationLocation {
+    pub station: Option<StationId>,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Elevation in meters.
+    pub elevation: Option<f64>,
+    pub begin: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
+
+impl StationLocation {
+    /// The distinct station/latitude/longitude/elevation combinations seen in `records`,
+    /// each with the dates of the first and last record reported from it.
+    pub fn from_records<'a, I>(records: I) -> Vec<StationLocation>
+    where
+        I: IntoIterator<Item = &'a Record>,
+    {
+        let mut seen: HashMap<(Option<StationId>, u64, u64, u64), StationLocation> = HashMap::new();
+        for record in records {
+            let (latitude, longitude) = match (record.latitude(), record.longitude()) {
+                (Some(lat), Some(lon)) => (lat, lon),
+                _ => continue,
+            };
+            let station = record.station().copied();
+            let date = record.date().date();
+            let key = (
+                station,
+                latitude.to_bits(),
+                longitude.to_bits(),
+                record.elevation().to_bits(),
+            );
+            let location = seen.entry(key).or_insert(StationLocation {
+                station,
+                latitude,
+                longitude,
+                elevation: Some(record.elevation()),
+                begin: Some(date),
+                end: Some(date),
+            });
+            location.begin = location.begin.min(Some(date));
+            location.end = location.end.max(Some(date));
+        }
+        let mut locations: Vec<StationLocation> = seen.into_values().collect();
+        locations.sort_by(|a, b| a.station.cmp(&b.station).then(a.begin.cmp(&b.begin)));
+        locations
+    }
+
+    /// The location of an `isd-history.csv` entry, `None` when it has no coordinates.
+    pub fn from_history(station: &StationHistory) -> Option<StationLocation> {
+        Some(StationLocation {
+            station: Some(*station.id()),
+            latitude: station.latitude()?,
+            longitude: station.longitude()?,
+            elevation: station.elevation(),
+            begin: Some(station.begin()),
+            end: Some(station.end()),
+        })
+    }
+}
+
+/// A station found by a `NearestQuery`, with its distance from the query point in kilometers.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Neighbor {
+    pub location: StationLocation,
+    pub distance: f64,
+}
+
+/// A search for the stations closest to a site.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearestQuery {
+    latitude: f64,
+    longitude: f64,
+    elevation: Option<(f64, f64)>,
+    period: Option<(NaiveDate, NaiveDate)>,
+    max_distance: Option<f64>,
+}
+
+impl NearestQuery {
+    pub fn new(latitude: f64, longitude: f64) -> NearestQuery {
+        NearestQuery {
+            latitude,
+            longitude,
+            elevation: None,
+            period: None,
+            max_distance: None,
+        }
+    }
+
+    /// Only keep stations within `max_difference` meters of the site `elevation`.
+    /// Stations without a known elevation are dropped.
+    pub fn elevation_within(mut self, elevation: f64, max_difference: f64) -> NearestQuery {
+        self.elevation = Some((elevation, max_difference));
+        self
+    }
+
+    /// Only keep stations whose period of record overlaps `start..=end`.
+    /// Stations without a known period are dropped.
+    pub fn active_between(mut self, start: NaiveDate, end: NaiveDate) -> NearestQuery {
+        self.period = Some((start, end));
+        self
+    }
+
+    /// Only keep stations within `distance` kilometers of the site.
+    pub fn within(mut self, distance: f64) -> NearestQuery {
+        self.max_distance = Some(distance);
+        self
+    }
+
+    fn accepts(&self, location: &StationLocation) -> bool {
+        if let Some((elevation, max_difference)) = self.elevation {
+            match location.elevation {
+                Some(e) if (e - elevation).abs() <= max_difference => {}
+                _ => return false,
+            }
+        }
+        if let Some((start, end)) = self.period {
+            match (location.begin, location.end) {
+                (Some(begin), Some(last)) if begin <= end && last >= start => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// The `k` closest candidates that pass the filters, nearest first.
+    pub fn search<I>(&self, candidates: I, k: usize) -> Vec<Neighbor>
+    where
+        I: IntoIterator<Item = StationLocation>,
+    {
+        let mut neighbors: Vec<Neighbor> = candidates
+            .into_iter()
+            .filter(|location| self.accepts(location))
+            .map(|location| Neighbor {
+                distance: haversine_distance(
+                    self.latitude,
+                    self.longitude,
+                    location.latitude,
+                    location.longitude,
+                ),
+                location,
+            })
+            .filter(|n| self.max_distance.is_none_or(|max| n.distance <= max))
+            .collect();
+        neighbors.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        neighbors.truncate(k);
+        neighbors
+    }
+
+    /// The `k` closest stations of a catalog. Stations without coordinates are skipped.
+    pub fn search_catalog(&self, catalog: &StationCatalog, k: usize) -> Vec<Neighbor> {
+        self.search(catalog.iter().filter_map(StationLocation::from_history), k)
+    }
+
+    /// The `k` closest locations seen in a stream of parsed records.
+    pub fn search_records<'a, I>(&self, records: I, k: usize) -> Vec<Neighbor>
+    where
+        I: IntoIterator<Item = &'a Record>,
+    {
+        self.search(StationLocation::from_records(records), k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{haversine_distance, NearestQuery, StationLocation};
+    use chrono::NaiveDate;
+
+    fn location(latitude: f64, longitude: f64, elevation: f64, end_year: i32) -> StationLocation {
+        StationLocation {
+            station: None,
+            latitude,
+            longitude,
+            elevation: Some(elevation),
+            begin: NaiveDate::from_ymd_opt(1973, 1, 1),
+            end: NaiveDate::from_ymd_opt(end_year, 12, 31),
+        }
+    }
+
+    #[test]
+    fn test_haversine_distance() {
+        // JFK to LAX is about 3975 km
+        let d = haversine_distance(40.6398, -73.7789, 33.9425, -118.4081);
+        assert!((d - 3975.0).abs() < 5.0);
+        assert_eq!(haversine_distance(10.0, 20.0, 10.0, 20.0), 0.0);
+    }
+
+    #[test]
+    fn test_nearest_query() {
+        let candidates = vec![
+            location(40.779, -73.880, 3.4, 2024),   // La Guardia
+            location(40.639, -73.762, 3.4, 2024),   // JFK
+            location(40.683, -74.169, 2.1, 1990),   // Newark, closed in this example
+            location(41.067, -73.708, 132.9, 2024), // White Plains
+        ];
+        let central_park = NearestQuery::new(40.779, -73.969);
+
+        let nearest = central_park.search(candidates.clone(), 2);
+        assert_eq!(nearest.len(), 2);
+        assert_eq!(nearest[0].location.latitude, 40.779);
+        assert!(nearest[0].distance < nearest[1].distance);
+
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2020, 12, 31).unwrap();
+        let filtered = central_park
+            .clone()
+            .elevation_within(40.0, 50.0)
+            .active_between(start, end)
+            .search(candidates, 10);
+        assert_eq!(filtered.len(), 2);
+        assert!(filtered.iter().all(|n| n.location.elevation == Some(3.4)));
+    }
+}
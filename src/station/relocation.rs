@@ -0,0 +1,242 @@
+use crate::model::Record;
+use crate::station::nearest::haversine_distance;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+/// How different a row has to be from the current location before it is treated as a move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RelocationThresholds {
+    /// Horizontal distance in kilometers. ISD coordinates are rounded differently by
+    /// different sources, so very small values split segments on noise.
+    pub distance: f64,
+    /// Elevation difference in meters.
+    pub elevation: f64,
+    /// Whether a change of station name alone starts a new segment.
+    pub name: bool,
+    /// Consecutive rows that have to agree on a new location before it is accepted,
+    /// so that single mis-keyed rows do not show up as moves.
+    pub min_observations: usize,
+}
+
+impl Default for RelocationThresholds {
+    fn default() -> Self {
+        RelocationThresholds {
+            distance: 0.2,
+            elevation: 5.0,
+            name: true,
+            min_observations: 3,
+        }
+    }
+}
+
+/// A period over which a station reported from the same place under the same name.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LocationSegment {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub elevation: f64,
+    pub name: String,
+    pub begin: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub observations: usize,
+}
+
+impl LocationSegment {
+    fn start(record: &Record, latitude: f64, longitude: f64) -> LocationSegment {
+        LocationSegment {
+            latitude,
+            longitude,
+            elevation: record.elevation(),
+            name: record.name().to_string(),
+            begin: record.date(),
+            end: record.date(),
+            observations: 1,
+        }
+    }
+
+    fn matches(&self, other: &LocationSegment, thresholds: &RelocationThresholds) -> bool {
+        self.distance_to(other) <= thresholds.distance
+            && (self.elevation - other.elevation).abs() <= thresholds.elevation
+            && (!thresholds.name || self.name == other.name)
+    }
+
+    /// Distance in kilometers between the two segments.
+    pub fn distance_to(&self, other: &LocationSegment) -> f64 {
+        haversine_distance(
+            self.latitude,
+            self.longitude,
+            other.latitude,
+            other.longitude,
+        )
+    }
+
+    fn extend(&mut self, other: &LocationSegment) {
+        self.begin = self.begin.min(other.begin);
+        self.end = self.end.max(other.end);
+        self.observations += other.observations;
+    }
+}
+
+/// A change between two consecutive `LocationSegment`s.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Breakpoint {
+    /// The first observation at the new location.
+    pub date: NaiveDateTime,
+    /// Horizontal distance moved in kilometers.
+    pub distance: f64,
+    /// Elevation change in meters, positive when the station moved up.
+    pub elevation_change: f64,
+    pub name_changed: bool,
+}
+
+/// Adds a row that doesn't match the current segment to the candidate for a new one,
+/// returning the previous candidate when the row doesn't match it either.
+fn advance(
+    candidate: &mut Option<LocationSegment>,
+    row: LocationSegment,
+    thresholds: &RelocationThresholds,
+) -> Option<LocationSegment> {
+    match candidate {
+        Some(c) if c.matches(&row, thresholds) => {
+            c.extend(&row);
+            None
+        }
+        _ => candidate.replace(row),
+    }
+}
+
+/// Splits a station's records into the segments it reported from, oldest first.
+///
+/// Records are expected to belong to one station and be in chronological order.
+/// Rows without coordinates are skipped. Every location, the first one included, has to be
+/// seen in `min_observations` consecutive rows before it starts a segment, and rows that
+/// don't make it are folded into the segment they interrupt, or the first segment when they
+/// come before it. When no location is ever confirmed, the last one seen is used.
+pub fn location_timeline<'a, I>(
+    records: I,
+    thresholds: &RelocationThresholds,
+) -> Vec<LocationSegment>
+where
+    I: IntoIterator<Item = &'a Record>,
+{
+    let mut segments: Vec<LocationSegment> = Vec::new();
+    let mut candidate: Option<LocationSegment> = None;
+    // rows before the first confirmed location
+    let mut leading: Option<LocationSegment> = None;
+
+    for record in records {
+        let (latitude, longitude) = match (record.latitude(), record.longitude()) {
+            (Some(lat), Some(lon)) => (lat, lon),
+            _ => continue,
+        };
+        let row = LocationSegment::start(record, latitude, longitude);
+
+        if let Some(current) = segments.last_mut() {
+            if current.matches(&row, thresholds) {
+                // a short excursion that came back is noise, fold it into the current segment
+                if let Some(noise) = candidate.take() {
+                    current.extend(&noise);
+                }
+                current.extend(&row);
+                continue;
+            }
+        }
+        // a different excursion replaces the previous one, which is again noise
+        if let Some(noise) = advance(&mut candidate, row, thresholds) {
+            match segments.last_mut().or(leading.as_mut()) {
+                Some(segment) => segment.extend(&noise),
+                None => leading = Some(noise),
+            }
+        }
+        if let Some(mut c) = candidate.take_if(|c| c.observations >= thresholds.min_observations) {
+            if let Some(noise) = leading.take() {
+                c.extend(&noise);
+            }
+            segments.push(c);
+        }
+    }
+    match (segments.last_mut(), candidate) {
+        (Some(current), Some(noise)) => current.extend(&noise),
+        (None, Some(mut c)) => {
+            if let Some(noise) = leading {
+                c.extend(&noise);
+            }
+            segments.push(c);
+        }
+        _ => {}
+    }
+    segments
+}
+
+/// The relocations between consecutive segments of a timeline.
+pub fn breakpoints(segments: &[LocationSegment]) -> Vec<Breakpoint> {
+    segments
+        .windows(2)
+        .map(|pair| Breakpoint {
+            date: pair[1].begin,
+            distance: pair[0].distance_to(&pair[1]),
+            elevation_change: pair[1].elevation - pair[0].elevation,
+            name_changed: pair[0].name != pair[1].name,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::record;
+
+    /// Hourly records at each of the given coordinates in turn.
+    fn records(locations: &[(f64, f64)]) -> Vec<Record> {
+        locations
+            .iter()
+            .enumerate()
+            .map(|(hour, (latitude, longitude))| {
+                record(&[
+                    ("DATE", &format!("2020-01-01T{:02}:51:00", hour)),
+                    ("LATITUDE", &latitude.to_string()),
+                    ("LONGITUDE", &longitude.to_string()),
+                ])
+            })
+            .collect()
+    }
+
+    const HERE: (f64, f64) = (40.77, -73.88);
+    const THERE: (f64, f64) = (40.85, -73.88);
+
+    #[test]
+    fn test_relocation() {
+        let records = records(&[HERE, HERE, HERE, HERE, THERE, THERE, THERE, THERE]);
+        let segments = location_timeline(&records, &RelocationThresholds::default());
+        assert_eq!(segments.len(), 2);
+        assert_eq!(
+            segments.iter().map(|s| s.observations).collect::<Vec<_>>(),
+            vec![4, 4]
+        );
+        let breakpoints = breakpoints(&segments);
+        assert_eq!(breakpoints.len(), 1);
+        assert_eq!(breakpoints[0].date, records[4].date());
+        assert!((breakpoints[0].distance - 8.9).abs() < 0.1);
+        assert!(!breakpoints[0].name_changed);
+    }
+
+    #[test]
+    fn test_outlier_at_start() {
+        let records = records(&[THERE, HERE, HERE, HERE, HERE]);
+        let segments = location_timeline(&records, &RelocationThresholds::default());
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].latitude, segments[0].longitude), HERE);
+        assert_eq!(segments[0].begin, records[0].date());
+        assert_eq!(segments[0].observations, 5);
+        assert!(breakpoints(&segments).is_empty());
+    }
+
+    #[test]
+    fn test_outlier_in_middle() {
+        let records = records(&[HERE, HERE, HERE, THERE, HERE, HERE, HERE]);
+        let segments = location_timeline(&records, &RelocationThresholds::default());
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].latitude, segments[0].longitude), HERE);
+        assert_eq!(segments[0].observations, 7);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::derived::crosswind::gust_speed;
+use crate::fields::optional::weather_occurrence::AUX;
+use crate::model::Record;
+use crate::series::precipitation::daily_total;
+use crate::series::resample::Element;
+use crate::station::id::StationId;
+use crate::summary::Mean;
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The GSOD FRSHTT indicators, whether each was reported at any time during the day.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Frshtt {
+    pub fog: bool,
+    /// Rain or drizzle.
+    pub rain: bool,
+    /// Snow or ice pellets.
+    pub snow: bool,
+    /// Hail or small hail.
+    pub hail: bool,
+    pub thunder: bool,
+    /// Tornado or funnel cloud.
+    pub tornado: bool,
+}
+
+impl Frshtt {
+    /// Adds the weather of an `AUX` element.
+    pub fn add_aux(&mut self, aux: &AUX) {
+        self.fog |= aux.obscuration_code() == "2";
+        self.rain |= matches!(aux.precipitation_code(), "01" | "02");
+        self.snow |= matches!(aux.precipitation_code(), "03" | "04" | "05" | "06");
+        self.hail |= matches!(aux.precipitation_code(), "07" | "08");
+        self.thunder |= aux.descriptor_code() == "7";
+        self.tornado |= aux.weather_phenomena_code() == "3";
+    }
+
+    /// Adds a WMO present weather (ww) code of a manual `MWX` observation.
+    pub fn add_present_weather(&mut self, code: u8) {
+        self.fog |= matches!(code, 11 | 12 | 40..=49);
+        self.rain |= matches!(code, 50..=69 | 80..=84 | 91 | 92);
+        self.snow |= matches!(code, 68..=79 | 83..=86 | 93 | 94);
+        self.hail |= matches!(code, 87..=90 | 96 | 99);
+        self.thunder |= matches!(code, 17 | 29 | 91..=99);
+        self.tornado |= code == 19;
+    }
+
+    /// Adds a WMO automated present weather code of an `AWX` observation.
+    pub fn add_automated_weather(&mut self, code: u8) {
+        self.fog |= matches!(code, 20 | 30..=35);
+        self.rain |= matches!(code, 22 | 23 | 25 | 43 | 44 | 47 | 48 | 50..=68 | 81..=84 | 92 | 95);
+        self.snow |= matches!(code, 24 | 45 | 46 | 67 | 68 | 70..=78 | 85..=87);
+        self.hail |= matches!(code, 89 | 93 | 96);
+        self.thunder |= matches!(code, 26 | 90..=96);
+        self.tornado |= code == 99;
+    }
+
+    pub fn add_record(&mut self, record: &Record) {
+        record.aux().for_each(|aux| self.add_aux(aux));
+        record
+            .mwx()
+            .filter_map(|mw| mw.atmospheric_condition_code().parse().ok())
+            .for_each(|code| self.add_present_weather(code));
+        record
+            .awx()
+            .filter_map(|aw| aw.atmospheric_condition_code().parse().ok())
+            .for_each(|code| self.add_automated_weather(code));
+    }
+}
+
+impl fmt::Display for Frshtt {
+    /// The six digit GSOD form, e.g. `010010` for rain and thunder.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for flag in [
+            self.fog,
+            self.rain,
+            self.snow,
+            self.hail,
+            self.thunder,
+            self.tornado,
+        ] {
+            write!(f, "{}", flag as u8)?;
+        }
+        Ok(())
+    }
+}
+
+/// The precipitation of a day from the `AAX` groups.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct DailyPrecipitation {
+    /// Total in mm.
+    pub total: f64,
+    /// Whether only a trace was reported.
+    pub trace: bool,
+    /// The accumulation period of the reports the total is made of, in hours.
+    pub period: i8,
+    /// The hours covered by the reports, at most 24.
+    pub hours: i32,
+}
+
+impl DailyPrecipitation {
+    /// The accumulations are totalled by `series::precipitation::daily_total`. As with GSOD
+    /// the reports are those made during the day, so they can include precipitation from the
+    /// evening before.
+    pub fn from_records(records: &[&Record]) -> Option<DailyPrecipitation> {
+        let accumulations = records.iter().flat_map(|record| {
+            record.aax().filter_map(|aa| {
+                let period = aa.period_quantity()?;
+                let depth = aa.depth_dimension()?;
+                Some((period, record.date(), depth, aa.condition_code() == "2"))
+            })
+        });
+        daily_total(accumulations).map(|t| DailyPrecipitation {
+            total: t.total,
+            trace: t.trace,
+            period: t.period,
+            hours: t.hours,
+        })
+    }
+}
+
+/// A GSOD style summary of one station day. Means carry the number of observations they
+/// are made of, and each element is `None` when it was never reported.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DailySummary {
+    pub station: Option<StationId>,
+    pub date: NaiveDate,
+    pub observations: usize,
+    /// The observations with an hourly element, leaving out the `SOD` and `SOM` summaries.
+    pub hourly_observations: usize,
+    /// Air temperature in °C.
+    pub temperature: Option<Mean>,
+    /// Highest and lowest hourly air temperature in °C.
+    pub max_temperature: Option<f64>,
+    pub min_temperature: Option<f64>,
+    pub dew_point: Option<Mean>,
+    /// Sea level pressure in hPa.
+    pub sea_level_pressure: Option<Mean>,
+    pub max_sea_level_pressure: Option<f64>,
+    pub min_sea_level_pressure: Option<f64>,
+    /// Station pressure in hPa, from `MA1` reports only.
+    pub station_pressure: Option<Mean>,
+    /// Visibility in meters.
+    pub visibility: Option<Mean>,
+    /// Wind speed in m/s, calm observations counting as 0.
+    pub wind_speed: Option<Mean>,
+    pub max_wind_speed: Option<f64>,
+    /// Highest gust in m/s, see `derived::crosswind::gust_speed`.
+    pub max_gust: Option<f64>,
+    pub precipitation: Option<DailyPrecipitation>,
+    /// The last reported snow depth of the day in cm.
+    pub snow_depth: Option<i16>,
+    pub frshtt: Frshtt,
+}
+
+fn max(values: impl Iterator<Item = f64>) -> Option<f64> {
+    values.reduce(f64::max)
+}
+
+fn min(values: impl Iterator<Item = f64>) -> Option<f64> {
+    values.reduce(f64::min)
+}
+
+impl DailySummary {
+    /// Summarizes the records of one station and day, which should be in date order.
+    pub fn from_records(
+        station: Option<StationId>,
+        date: NaiveDate,
+        records: &[&Record],
+    ) -> DailySummary {
+        let temperatures = || records.iter().filter_map(|r| r.tmp().air_temperature());
+        let sea_level_pressures = || records.iter().filter_map(|r| r.slp().pressure());
+        let wind_speeds = || {
+            records.iter().filter_map(|r| {
+                if r.wnd().is_calm() {
+                    Some(0.0)
+                } else {
+                    r.wnd().speed_rate()
+                }
+            })
+        };
+        let mut frshtt = Frshtt::default();
+        records.iter().for_each(|r| frshtt.add_record(r));
+
+        DailySummary {
+            station,
+            date,
+            observations: records.len(),
+            hourly_observations: records
+                .iter()
+                .filter(|r| !matches!(r.report_type(), Some("SOD" | "SOM")))
+                .filter(|r| Element::ALL.iter().any(|e| e.from_record(r).is_some()))
+                .count(),
+            temperature: Mean::of(temperatures()),
+            max_temperature: max(temperatures()),
+            min_temperature: min(temperatures()),
+            dew_point: Mean::of(
+                records
+                    .iter()
+                    .filter_map(|r| r.dew().dew_point_temperature()),
+            ),
+            sea_level_pressure: Mean::of(sea_level_pressures()),
+            max_sea_level_pressure: max(sea_level_pressures()),
+            min_sea_level_pressure: min(sea_level_pressures()),
+            station_pressure: Mean::of(
+                records
+                    .iter()
+                    .filter_map(|r| r.ma1().and_then(|ma1| ma1.station_pressure())),
+            ),
+            visibility: Mean::of(
+                records
+                    .iter()
+                    .filter_map(|r| r.vis().distance().map(f64::from)),
+            ),
+            wind_speed: Mean::of(wind_speeds()),
+            max_wind_speed: max(wind_speeds()),
+            max_gust: max(records.iter().filter_map(|r| gust_speed(r))),
+            precipitation: DailyPrecipitation::from_records(records),
+            snow_depth: records
+                .iter()
+                .rev()
+                .find_map(|r| r.aj1().and_then(|aj1| aj1.depth_dimension())),
+            frshtt,
+        }
+    }
+}
+
+/// Summarizes records by station and UTC day, ordered by station and date.
+pub fn daily_summaries<'a, I>(records: I) -> Vec<DailySummary>
+where
+    I: IntoIterator<Item = &'a Record>,
+{
+    let mut days: BTreeMap<(Option<StationId>, NaiveDate), Vec<&Record>> = BTreeMap::new();
+    for record in records {
+        days.entry((record.station().copied(), record.date().date()))
+            .or_default()
+            .push(record);
+    }
+    days.into_iter()
+        .map(|((station, date), mut records)| {
+            records.sort_by_key(|r| r.date());
+            DailySummary::from_records(station, date, &records)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::records;
+
+    #[test]
+    fn test_daily_summary() {
+        let records = records(&[
+            &[
+                ("DATE", "2020-01-01T05:51:00"),
+                ("AA1", "01,0010,9,1"),
+                ("AA2", "06,0030,9,1"),
+                ("AJ1", "0005,1,1,999999,9,9"),
+                ("AU1", "1,0,02,0,0,1,1"),
+            ],
+            &[
+                ("DATE", "2020-01-01T06:51:00"),
+                ("WND", "999,9,C,0000,1"),
+                ("TMP", "+0011,1"),
+                ("SLP", "10120,1"),
+                ("AA1", "01,0020,9,1"),
+                ("AJ1", "0003,1,1,999999,9,9"),
+                ("MW1", "95,1"),
+                ("OC1", "0150,1"),
+            ],
+            &[
+                ("DATE", "2020-01-01T11:51:00"),
+                ("WND", "270,1,N,0080,1"),
+                ("VIS", "008000,1,9,9"),
+                ("TMP", "-0012,1"),
+                ("SLP", "99999,9"),
+                ("AA2", "06,0010,9,1"),
+            ],
+            &[("DATE", "2020-01-02T00:51:00")],
+        ]);
+        let summaries = daily_summaries(&records);
+        assert_eq!(summaries.len(), 2);
+        let day = &summaries[0];
+        assert_eq!((day.observations, day.hourly_observations), (3, 3));
+        assert!((day.temperature.unwrap().value - 2.0).abs() < 1e-9);
+        assert_eq!(day.max_temperature, Some(6.1));
+        assert_eq!(day.min_temperature, Some(-1.2));
+        assert_eq!(day.sea_level_pressure.unwrap().count, 2);
+        assert_eq!(day.wind_speed.unwrap().count, 3);
+        assert_eq!(day.max_wind_speed, Some(8.0));
+        assert_eq!(day.max_gust, Some(15.0));
+        // two 6 hour reports cover more of the day than two 1 hour reports
+        let precipitation = day.precipitation.unwrap();
+        assert_eq!(precipitation.period, 6);
+        assert!((precipitation.total - 4.0).abs() < 1e-9);
+        assert_eq!(day.snow_depth, Some(3));
+        assert_eq!(day.frshtt.to_string(), "010010");
+    }
+
+    #[test]
+    fn test_daily_precipitation() {
+        let six_hour = records(&[
+            // the METAR and SYNOP 6 hour totals ending at 06 are the same report
+            &[("DATE", "2020-01-01T05:51:00"), ("AA1", "06,0030,9,1")],
+            &[
+                ("DATE", "2020-01-01T06:00:00"),
+                ("SOURCE", "4"),
+                ("REPORT_TYPE", "FM-12"),
+                ("AA1", "06,0030,9,1"),
+            ],
+            // overlaps the 6 hours ending at 06
+            &[("DATE", "2020-01-01T08:51:00"), ("AA1", "06,0050,9,1")],
+            &[("DATE", "2020-01-01T11:51:00"), ("AA1", "06,0010,9,1")],
+        ]);
+        // the 24 hour total of the last METAR is repeated by the daily summary
+        let daily = records(&[
+            &[("DATE", "2020-01-01T23:51:00"), ("AA1", "24,0123,9,1")],
+            &[
+                ("DATE", "2020-01-01T23:59:00"),
+                ("REPORT_TYPE", "SOD"),
+                ("AA1", "24,0123,9,1"),
+            ],
+        ]);
+        let precipitation = |records: &[Record]| {
+            let records: Vec<&Record> = records.iter().collect();
+            DailyPrecipitation::from_records(&records).unwrap()
+        };
+
+        let precipitation_6 = precipitation(&six_hour);
+        assert!((precipitation_6.total - 4.0).abs() < 1e-9);
+        assert_eq!(precipitation_6.period, 6);
+        assert_eq!(precipitation_6.hours, 12);
+        let precipitation_24 = precipitation(&daily);
+        assert!((precipitation_24.total - 12.3).abs() < 1e-9);
+        assert_eq!(precipitation_24.hours, 24);
+    }
+
+    #[test]
+    fn test_automated_weather() {
+        let mut frshtt = Frshtt::default();
+        // drizzle
+        frshtt.add_automated_weather(22);
+        assert_eq!(frshtt.to_string(), "010000");
+        frshtt.add_automated_weather(30);
+        frshtt.add_automated_weather(26);
+        assert_eq!(frshtt.to_string(), "110010");
+    }
+}
@@ -0,0 +1,528 @@
+use crate::derived::pressure::{sea_level_to_station_pressure, STANDARD_PRESSURE};
+use crate::derived::psychrometrics::{mixing_ratio, vapor_pressure};
+use crate::derived::wet_bulb::wet_bulb_temperature;
+use crate::model::Record;
+use crate::series::resample::{resample, HourlyObservation, ResamplePriority};
+use crate::station::id::StationId;
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+
+/// The annual percentages of hours that heating design values are below.
+pub const HEATING_PERCENTS: [f64; 2] = [99.6, 99.0];
+/// The annual percentages of hours that cooling design values are exceeded.
+pub const COOLING_PERCENTS: [f64; 3] = [0.4, 1.0, 2.0];
+/// The annual percentages of hours that extreme wind speeds are exceeded.
+pub const WIND_PERCENTS: [f64; 3] = [1.0, 2.5, 5.0];
+/// The return periods in years of the extreme dry-bulb temperatures.
+pub const RETURN_PERIODS: [u32; 4] = [5, 10, 20, 50];
+/// The fraction of its hours a year needs a dry-bulb temperature in for its highest and
+/// lowest dry-bulb to count as annual extremes.
+pub const ANNUAL_EXTREME_COMPLETENESS: f64 = 0.85;
+
+/// Half the width of the bin of hours whose mean coincident values are taken, in °C.
+const COINCIDENT_BIN: f64 = 0.5;
+
+/// A design value in °C with the mean of another element in the hours at that value, e.g.
+/// a dry-bulb temperature and its mean coincident wet-bulb temperature.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Coincident {
+    pub percent: f64,
+    pub value: f64,
+    pub mean_coincident: Option<f64>,
+}
+
+/// A dew point design value with its humidity ratio in g/kg and mean coincident dry-bulb.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct DewPointDesign {
+    pub percent: f64,
+    pub dew_point: f64,
+    pub humidity_ratio: f64,
+    pub mean_coincident_dry_bulb: Option<f64>,
+}
+
+/// The annual heating and humidification design conditions.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HeatingDesign {
+    pub coldest_month: Option<u32>,
+    /// Dry-bulb temperatures at `HEATING_PERCENTS`.
+    pub dry_bulb: Vec<Coincident>,
+    /// Dew points at `HEATING_PERCENTS`.
+    pub humidification: Vec<DewPointDesign>,
+    /// Mean wind speed in m/s and prevailing direction in degrees in the hours at or below
+    /// the 99.6% dry-bulb.
+    pub mean_coincident_wind_speed: Option<f64>,
+    pub prevailing_coincident_wind_direction: Option<f64>,
+}
+
+/// The annual cooling and dehumidification design conditions.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CoolingDesign {
+    pub hottest_month: Option<u32>,
+    /// The mean daily dry-bulb range of the hottest month.
+    pub hottest_month_dry_bulb_range: Option<f64>,
+    /// Dry-bulb temperatures at `COOLING_PERCENTS` with mean coincident wet-bulb.
+    pub dry_bulb: Vec<Coincident>,
+    /// Wet-bulb temperatures at `COOLING_PERCENTS` with mean coincident dry-bulb.
+    pub wet_bulb: Vec<Coincident>,
+    /// Dew points at `COOLING_PERCENTS`.
+    pub dehumidification: Vec<DewPointDesign>,
+    /// Mean wind speed in m/s and prevailing direction in degrees in the hours at or above
+    /// the 0.4% dry-bulb.
+    pub mean_coincident_wind_speed: Option<f64>,
+    pub prevailing_coincident_wind_direction: Option<f64>,
+}
+
+/// A dry-bulb temperature expected once in `years` years.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct ReturnPeriod {
+    pub years: u32,
+    pub max: f64,
+    pub min: f64,
+}
+
+/// The extreme annual design conditions.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ExtremeDesign {
+    /// Wind speeds in m/s at `WIND_PERCENTS`.
+    pub wind_speed: Vec<Coincident>,
+    /// Mean and standard deviation of the annual highest and lowest dry-bulb, over the
+    /// years complete to `ANNUAL_EXTREME_COMPLETENESS`. A partial year could miss its
+    /// hottest or coldest days.
+    pub mean_max_dry_bulb: Option<f64>,
+    pub mean_min_dry_bulb: Option<f64>,
+    pub std_dev_max_dry_bulb: Option<f64>,
+    pub std_dev_min_dry_bulb: Option<f64>,
+    /// From a Gumbel distribution of the annual extremes.
+    pub return_periods: Vec<ReturnPeriod>,
+}
+
+/// ASHRAE style design conditions of a station, laid out as in the climatic design tables
+/// of the Handbook of Fundamentals.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DesignConditions {
+    pub station: Option<StationId>,
+    pub start_year: i32,
+    pub end_year: i32,
+    /// The hours with a dry-bulb temperature.
+    pub hours: usize,
+    pub heating: HeatingDesign,
+    pub cooling: CoolingDesign,
+    pub extreme: ExtremeDesign,
+}
+
+/// The value below which `percent` of the sorted values lie, interpolated linearly.
+fn percentile(sorted: &[f64], percent: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = percent / 100.0 * (sorted.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64))
+}
+
+fn sorted(values: impl Iterator<Item = f64>) -> Vec<f64> {
+    let mut values: Vec<f64> = values.collect();
+    values.sort_by(f64::total_cmp);
+    values
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        None
+    } else {
+        Some(sum / count as f64)
+    }
+}
+
+fn sample_std_dev(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance =
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+/// The most frequent wind direction in 10 degree sectors, as the sector center.
+fn prevailing_direction(directions: impl Iterator<Item = f64>) -> Option<f64> {
+    let mut sectors: BTreeMap<i64, usize> = BTreeMap::new();
+    for direction in directions {
+        let sector = ((direction / 10.0).round() as i64 - 1).rem_euclid(36) + 1;
+        *sectors.entry(sector).or_default() += 1;
+    }
+    sectors
+        .into_iter()
+        .max_by_key(|(sector, count)| (*count, -sector))
+        .map(|(sector, _)| sector as f64 * 10.0)
+}
+
+/// One hour's psychrometric values.
+struct Hour {
+    month: u32,
+    date: NaiveDate,
+    dry_bulb: f64,
+    dew_point: Option<f64>,
+    wet_bulb: Option<f64>,
+    pressure: f64,
+    wind_speed: Option<f64>,
+    wind_direction: Option<f64>,
+}
+
+impl DesignConditions {
+    /// Computes the design conditions of one station from its hourly observations, such as
+    /// those of `series::resample::resample`. The station pressure of an hour is used for
+    /// its wet-bulb and humidity ratio when reported, else the standard atmosphere pressure
+    /// at `elevation` meters. `None` when no hour has a dry-bulb temperature.
+    pub fn from_observations(
+        observations: &[HourlyObservation],
+        elevation: f64,
+    ) -> Option<DesignConditions> {
+        let standard_pressure = sea_level_to_station_pressure(STANDARD_PRESSURE, elevation, None);
+        let hours: Vec<Hour> = observations
+            .iter()
+            .filter_map(|o| {
+                let dry_bulb = o.temperature.as_ref()?.value;
+                let dew_point = o.dew_point.as_ref().map(|d| d.value.min(dry_bulb));
+                let pressure = o
+                    .station_pressure
+                    .as_ref()
+                    .map_or(standard_pressure, |p| p.value);
+                Some(Hour {
+                    month: o.hour.month(),
+                    date: o.hour.date(),
+                    dry_bulb,
+                    dew_point,
+                    wet_bulb: dew_point.map(|d| wet_bulb_temperature(dry_bulb, d, pressure)),
+                    pressure,
+                    wind_speed: o.wind_speed.as_ref().map(|w| w.value),
+                    wind_direction: o.wind_direction.as_ref().map(|w| w.value),
+                })
+            })
+            .collect();
+        let (first, last) = (hours.first()?, hours.last()?);
+
+        let dry_bulbs = sorted(hours.iter().map(|h| h.dry_bulb));
+        let wet_bulbs = sorted(hours.iter().filter_map(|h| h.wet_bulb));
+        let dew_points = sorted(hours.iter().filter_map(|h| h.dew_point));
+        let coincident =
+            |value: f64, of: fn(&Hour) -> Option<f64>, other: fn(&Hour) -> Option<f64>| {
+                mean(
+                    hours
+                        .iter()
+                        .filter(|h| of(h).is_some_and(|v| (v - value).abs() <= COINCIDENT_BIN))
+                        .filter_map(other),
+                )
+            };
+        let design = |sorted: &[f64], percent: f64, exceeded: bool| {
+            percentile(sorted, if exceeded { 100.0 - percent } else { percent })
+        };
+        let dry_bulb_design = |percent: f64, exceeded: bool| {
+            design(&dry_bulbs, percent, exceeded).map(|value| Coincident {
+                percent,
+                value,
+                mean_coincident: coincident(value, |h| Some(h.dry_bulb), |h| h.wet_bulb),
+            })
+        };
+        let dew_point_design = |percent: f64, exceeded: bool| {
+            design(&dew_points, percent, exceeded).map(|dew_point| {
+                let pressure = mean(
+                    hours
+                        .iter()
+                        .filter(|h| h.dew_point.is_some())
+                        .map(|h| h.pressure),
+                )
+                .unwrap_or(standard_pressure);
+                DewPointDesign {
+                    percent,
+                    dew_point,
+                    humidity_ratio: mixing_ratio(vapor_pressure(dew_point), pressure),
+                    mean_coincident_dry_bulb: coincident(
+                        dew_point,
+                        |h| h.dew_point,
+                        |h| Some(h.dry_bulb),
+                    ),
+                }
+            })
+        };
+        let coincident_wind = |selected: &dyn Fn(&Hour) -> bool| {
+            (
+                mean(
+                    hours
+                        .iter()
+                        .filter(|h| selected(h))
+                        .filter_map(|h| h.wind_speed),
+                ),
+                prevailing_direction(
+                    hours
+                        .iter()
+                        .filter(|h| selected(h))
+                        .filter_map(|h| h.wind_direction),
+                ),
+            )
+        };
+
+        let monthly_means: BTreeMap<u32, f64> = (1..=12)
+            .filter_map(|month| {
+                mean(
+                    hours
+                        .iter()
+                        .filter(|h| h.month == month)
+                        .map(|h| h.dry_bulb),
+                )
+                .map(|m| (month, m))
+            })
+            .collect();
+        let coldest_month = monthly_means
+            .iter()
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(month, _)| *month);
+        let hottest_month = monthly_means
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(month, _)| *month);
+        let mut daily: BTreeMap<NaiveDate, (f64, f64)> = BTreeMap::new();
+        for hour in &hours {
+            let range = daily
+                .entry(hour.date)
+                .or_insert((hour.dry_bulb, hour.dry_bulb));
+            range.0 = range.0.max(hour.dry_bulb);
+            range.1 = range.1.min(hour.dry_bulb);
+        }
+        let hottest_month_dry_bulb_range = mean(
+            daily
+                .iter()
+                .filter(|(date, _)| Some(date.month()) == hottest_month)
+                .map(|(_, (max, min))| max - min),
+        );
+
+        let heating_dry_bulb = design(&dry_bulbs, HEATING_PERCENTS[0], true);
+        let (heating_wind_speed, heating_wind_direction) =
+            coincident_wind(&|h| heating_dry_bulb.is_some_and(|d| h.dry_bulb <= d));
+        let cooling_dry_bulb = design(&dry_bulbs, COOLING_PERCENTS[0], true);
+        let (cooling_wind_speed, cooling_wind_direction) =
+            coincident_wind(&|h| cooling_dry_bulb.is_some_and(|d| h.dry_bulb >= d));
+
+        let mut year_hours: BTreeMap<i32, usize> = BTreeMap::new();
+        for hour in &hours {
+            *year_hours.entry(hour.date.year()).or_default() += 1;
+        }
+        let complete = |year: i32| {
+            let days = NaiveDate::from_ymd_opt(year, 12, 31).map_or(365, |d| d.ordinal());
+            year_hours.get(&year).copied().unwrap_or_default() as f64
+                >= ANNUAL_EXTREME_COMPLETENESS * f64::from(days * 24)
+        };
+        let mut annual: BTreeMap<i32, (f64, f64)> = BTreeMap::new();
+        for (date, (max, min)) in daily.iter().filter(|(date, _)| complete(date.year())) {
+            let extremes = annual.entry(date.year()).or_insert((*max, *min));
+            extremes.0 = extremes.0.max(*max);
+            extremes.1 = extremes.1.min(*min);
+        }
+        let annual_max: Vec<f64> = annual.values().map(|(max, _)| *max).collect();
+        let annual_min: Vec<f64> = annual.values().map(|(_, min)| *min).collect();
+        let mean_max_dry_bulb = mean(annual_max.iter().copied());
+        let mean_min_dry_bulb = mean(annual_min.iter().copied());
+        let std_dev_max_dry_bulb = sample_std_dev(&annual_max);
+        let std_dev_min_dry_bulb = sample_std_dev(&annual_min);
+        let return_periods = match (
+            mean_max_dry_bulb,
+            mean_min_dry_bulb,
+            std_dev_max_dry_bulb,
+            std_dev_min_dry_bulb,
+        ) {
+            (Some(mean_max), Some(mean_min), Some(sd_max), Some(sd_min)) => RETURN_PERIODS
+                .iter()
+                .map(|years| {
+                    let n = f64::from(*years);
+                    let f = -(6f64.sqrt() / std::f64::consts::PI)
+                        * (0.5772 + (n / (n - 1.0)).ln().ln());
+                    ReturnPeriod {
+                        years: *years,
+                        max: mean_max + f * sd_max,
+                        min: mean_min - f * sd_min,
+                    }
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let wind_speeds = sorted(hours.iter().filter_map(|h| h.wind_speed));
+
+        Some(DesignConditions {
+            station: observations.first().and_then(|o| o.station),
+            start_year: first.date.year(),
+            end_year: last.date.year(),
+            hours: hours.len(),
+            heating: HeatingDesign {
+                coldest_month,
+                dry_bulb: HEATING_PERCENTS
+                    .iter()
+                    .filter_map(|p| dry_bulb_design(*p, true))
+                    .collect(),
+                humidification: HEATING_PERCENTS
+                    .iter()
+                    .filter_map(|p| dew_point_design(*p, true))
+                    .collect(),
+                mean_coincident_wind_speed: heating_wind_speed,
+                prevailing_coincident_wind_direction: heating_wind_direction,
+            },
+            cooling: CoolingDesign {
+                hottest_month,
+                hottest_month_dry_bulb_range,
+                dry_bulb: COOLING_PERCENTS
+                    .iter()
+                    .filter_map(|p| dry_bulb_design(*p, true))
+                    .collect(),
+                wet_bulb: COOLING_PERCENTS
+                    .iter()
+                    .filter_map(|p| {
+                        design(&wet_bulbs, *p, true).map(|value| Coincident {
+                            percent: *p,
+                            value,
+                            mean_coincident: coincident(
+                                value,
+                                |h| h.wet_bulb,
+                                |h| Some(h.dry_bulb),
+                            ),
+                        })
+                    })
+                    .collect(),
+                dehumidification: COOLING_PERCENTS
+                    .iter()
+                    .filter_map(|p| dew_point_design(*p, true))
+                    .collect(),
+                mean_coincident_wind_speed: cooling_wind_speed,
+                prevailing_coincident_wind_direction: cooling_wind_direction,
+            },
+            extreme: ExtremeDesign {
+                wind_speed: WIND_PERCENTS
+                    .iter()
+                    .filter_map(|p| {
+                        design(&wind_speeds, *p, true).map(|value| Coincident {
+                            percent: *p,
+                            value,
+                            mean_coincident: None,
+                        })
+                    })
+                    .collect(),
+                mean_max_dry_bulb,
+                mean_min_dry_bulb,
+                std_dev_max_dry_bulb,
+                std_dev_min_dry_bulb,
+                return_periods,
+            },
+        })
+    }
+}
+
+/// The design conditions of each station, from the records resampled to hours with the
+/// default `ResamplePriority`, ordered by station. Each station's elevation is taken from
+/// its records.
+pub fn design_conditions(records: &[Record]) -> Vec<DesignConditions> {
+    let elevations: BTreeMap<Option<StationId>, f64> = records
+        .iter()
+        .map(|r| (r.station().copied(), r.elevation()))
+        .collect();
+    resample(records, &ResamplePriority::default())
+        .chunk_by(|a, b| a.station == b.station)
+        .filter_map(|observations| {
+            let elevation = elevations
+                .get(&observations[0].station)
+                .copied()
+                .unwrap_or_default();
+            DesignConditions::from_observations(observations, elevation)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::series::resample::{HourlyValue, Provenance};
+    use chrono::{Duration, Timelike};
+
+    fn value(value: f64, hour: chrono::NaiveDateTime) -> Option<HourlyValue> {
+        Some(HourlyValue {
+            value,
+            date: hour,
+            report_type: None,
+            provenance: Provenance::Reported,
+        })
+    }
+
+    #[test]
+    fn test_design_conditions() {
+        let start = NaiveDate::from_ymd_opt(2019, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        // a seasonal cycle from -10 °C in January to 30 °C in July, with a 10 °C daily range
+        let observations: Vec<HourlyObservation> = (0..2 * 365 * 24)
+            .map(|h| {
+                let hour = start + Duration::hours(h);
+                let season = -(2.0 * std::f64::consts::PI * hour.ordinal0() as f64 / 365.0).cos();
+                let day = -(2.0 * std::f64::consts::PI * (hour.hour() as f64 - 3.0) / 24.0).cos();
+                let temperature = 10.0 + 20.0 * season + 5.0 * day;
+                let mut observation = HourlyObservation::from_reports(None, hour, &[]);
+                observation.temperature = value(temperature, hour);
+                observation.dew_point = value(temperature - 5.0, hour);
+                observation.wind_speed = value(if temperature < -10.0 { 8.0 } else { 3.0 }, hour);
+                observation.wind_direction =
+                    value(if temperature < -10.0 { 360.0 } else { 180.0 }, hour);
+                observation
+            })
+            .collect();
+        let design = DesignConditions::from_observations(&observations, 0.0).unwrap();
+        assert_eq!((design.start_year, design.end_year), (2019, 2020));
+        assert_eq!(design.heating.coldest_month, Some(1));
+        assert_eq!(design.cooling.hottest_month, Some(7));
+
+        let heating = design.heating.dry_bulb[0].value;
+        assert!(heating > -15.0 && heating < -13.0);
+        assert!(design.heating.dry_bulb[1].value > heating);
+        assert_eq!(
+            design.heating.prevailing_coincident_wind_direction,
+            Some(360.0)
+        );
+        assert_eq!(design.heating.mean_coincident_wind_speed, Some(8.0));
+
+        let cooling = &design.cooling.dry_bulb;
+        assert!(cooling[0].value > 30.0 && cooling[0].value < 35.0);
+        assert!(cooling[2].value < cooling[0].value);
+        // the wet-bulb is between the dew point and the dry-bulb
+        let wet_bulb = cooling[0].mean_coincident.unwrap();
+        assert!(wet_bulb > cooling[0].value - 5.0 && wet_bulb < cooling[0].value);
+        let dew_point = &design.cooling.dehumidification[0];
+        assert!((dew_point.dew_point - (cooling[0].value - 5.0)).abs() < 0.5);
+        assert!(dew_point.humidity_ratio > 20.0 && dew_point.humidity_ratio < 30.0);
+        assert!((design.cooling.hottest_month_dry_bulb_range.unwrap() - 10.0).abs() < 0.5);
+
+        assert_eq!(design.extreme.wind_speed[0].value, 8.0);
+        assert!((design.extreme.mean_max_dry_bulb.unwrap() - 35.0).abs() < 0.1);
+        assert_eq!(design.extreme.return_periods.len(), 4);
+
+        // a hot day in an otherwise missing year is not an annual extreme
+        let mut observations = observations;
+        let hot_day = NaiveDate::from_ymd_opt(2021, 7, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        observations.extend((0..24).map(|h| {
+            let hour = hot_day + Duration::hours(h);
+            let mut observation = HourlyObservation::from_reports(None, hour, &[]);
+            observation.temperature = value(45.0, hour);
+            observation
+        }));
+        let design = DesignConditions::from_observations(&observations, 0.0).unwrap();
+        assert_eq!(design.end_year, 2021);
+        assert!((design.extreme.mean_max_dry_bulb.unwrap() - 35.0).abs() < 0.1);
+    }
+}
@@ -0,0 +1,348 @@
+use crate::model::Record;
+use crate::station::id::StationId;
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+
+/// The elements kept in a record book.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtremeElement {
+    HighestTemperature,
+    LowestTemperature,
+    HighestDailyPrecipitation,
+    StrongestGust,
+    LowestPressure,
+}
+
+/// An extreme value, when it occurred and the field it was taken from, e.g. `TMP` or `KAX`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ExtremeValue {
+    pub value: f64,
+    pub date: NaiveDateTime,
+    pub field: &'static str,
+}
+
+/// The extremes of a period. Temperatures are in °C, precipitation in mm, gusts in m/s
+/// and sea level pressure in hPa. On a tie the earliest value added is kept.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct Extremes {
+    pub highest_temperature: Option<ExtremeValue>,
+    pub lowest_temperature: Option<ExtremeValue>,
+    pub highest_daily_precipitation: Option<ExtremeValue>,
+    pub strongest_gust: Option<ExtremeValue>,
+    pub lowest_pressure: Option<ExtremeValue>,
+}
+
+impl Extremes {
+    pub fn get(&self, element: ExtremeElement) -> Option<&ExtremeValue> {
+        match element {
+            ExtremeElement::HighestTemperature => self.highest_temperature.as_ref(),
+            ExtremeElement::LowestTemperature => self.lowest_temperature.as_ref(),
+            ExtremeElement::HighestDailyPrecipitation => self.highest_daily_precipitation.as_ref(),
+            ExtremeElement::StrongestGust => self.strongest_gust.as_ref(),
+            ExtremeElement::LowestPressure => self.lowest_pressure.as_ref(),
+        }
+    }
+
+    /// Keeps the candidate when it beats the current extreme, and returns whether it did.
+    pub fn add(&mut self, element: ExtremeElement, candidate: &ExtremeValue) -> bool {
+        let (current, highest) = match element {
+            ExtremeElement::HighestTemperature => (&mut self.highest_temperature, true),
+            ExtremeElement::LowestTemperature => (&mut self.lowest_temperature, false),
+            ExtremeElement::HighestDailyPrecipitation => {
+                (&mut self.highest_daily_precipitation, true)
+            }
+            ExtremeElement::StrongestGust => (&mut self.strongest_gust, true),
+            ExtremeElement::LowestPressure => (&mut self.lowest_pressure, false),
+        };
+        let beats = current.as_ref().is_none_or(|current| {
+            if highest {
+                candidate.value > current.value
+            } else {
+                candidate.value < current.value
+            }
+        });
+        if beats {
+            *current = Some(candidate.clone());
+        }
+        beats
+    }
+}
+
+/// The date of an `MK1` `DDHHMM` day and time within the month of the record.
+fn month_date_time(record: &Record, ddhhmm: isize) -> Option<NaiveDateTime> {
+    let date = record.date();
+    NaiveDate::from_ymd_opt(date.year(), date.month(), (ddhhmm / 10000) as u32)?.and_hms_opt(
+        (ddhhmm / 100 % 100) as u32,
+        (ddhhmm % 100) as u32,
+        0,
+    )
+}
+
+/// Whether a quality code marks a value as suspect or erroneous.
+fn is_flagged(quality_code: &str) -> bool {
+    matches!(quality_code, "2" | "3" | "6" | "7")
+}
+
+/// The values of a record that can be extremes.
+///
+/// Daily precipitation only comes from 24 hour `AAX` accumulations, so days without one
+/// are not considered. Maximum and minimum `KAX` temperatures are dated at the end of their
+/// period, and `MK1` pressures at the day and time they give. Values whose quality code
+/// marks them as suspect or erroneous are left out.
+pub fn candidates(record: &Record) -> Vec<(ExtremeElement, ExtremeValue)> {
+    let date = record.date();
+    let value = |value: f64, field: &'static str| ExtremeValue { value, date, field };
+    let mut candidates = Vec::new();
+
+    if let Some(t) = record
+        .tmp()
+        .air_temperature()
+        .filter(|_| !is_flagged(record.tmp().air_temperature_quality_code()))
+    {
+        candidates.push((ExtremeElement::HighestTemperature, value(t, "TMP")));
+        candidates.push((ExtremeElement::LowestTemperature, value(t, "TMP")));
+    }
+    for ka in record
+        .kax()
+        .filter(|ka| !is_flagged(ka.air_temperature_quality_code()))
+    {
+        let element = match ka.code() {
+            "M" | "P" => ExtremeElement::HighestTemperature,
+            "N" | "O" => ExtremeElement::LowestTemperature,
+            _ => continue,
+        };
+        if let Some(t) = ka.air_temperature() {
+            candidates.push((element, value(t, "KAX")));
+        }
+    }
+    for aa in record.aax().filter(|aa| !is_flagged(aa.quality_code())) {
+        if let (Some(24), Some(depth)) = (aa.period_quantity(), aa.depth_dimension()) {
+            candidates.push((
+                ExtremeElement::HighestDailyPrecipitation,
+                value(depth, "AAX"),
+            ));
+        }
+    }
+    if let Some(speed) = record
+        .oc1()
+        .filter(|oc1| !is_flagged(oc1.speed_quality_code()))
+        .and_then(|oc1| oc1.speed_rate())
+    {
+        candidates.push((ExtremeElement::StrongestGust, value(speed, "OC1")));
+    }
+    for oa in record
+        .oax()
+        .filter(|oa| matches!(oa.type_code(), "3" | "4"))
+        .filter(|oa| !is_flagged(oa.speed_quality_code()))
+    {
+        if let Some(speed) = oa.speed_rate() {
+            candidates.push((ExtremeElement::StrongestGust, value(speed, "OAX")));
+        }
+    }
+    for oe in record
+        .oex()
+        .filter(|oe| oe.type_code() == "1" && !is_flagged(oe.quality_code()))
+    {
+        if let Some(speed) = oe.speed_rate() {
+            candidates.push((ExtremeElement::StrongestGust, value(speed, "OEX")));
+        }
+    }
+    if let Some(p) = record
+        .slp()
+        .pressure()
+        .filter(|_| !is_flagged(record.slp().pressure_quality_code()))
+    {
+        candidates.push((ExtremeElement::LowestPressure, value(p, "SLP")));
+    }
+    if let Some(mk1) = record
+        .mk1()
+        .filter(|mk1| !is_flagged(mk1.min_sea_level_pressure_month_quality_code()))
+    {
+        let date = mk1
+            .min_sea_level_pressure_month_date_time()
+            .and_then(|d| month_date_time(record, d));
+        if let (Some(p), Some(date)) = (mk1.min_sea_level_pressure_month(), date) {
+            candidates.push((
+                ExtremeElement::LowestPressure,
+                ExtremeValue {
+                    value: p,
+                    date,
+                    field: "MK1",
+                },
+            ));
+        }
+    }
+    candidates
+}
+
+/// The extremes of a station for all time, for each month and for each calendar day,
+/// updated as records are added.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RecordBook {
+    pub station: Option<StationId>,
+    pub all_time: Extremes,
+    /// By month number.
+    pub monthly: BTreeMap<u32, Extremes>,
+    /// By month number and day of the month.
+    pub daily: BTreeMap<u32, BTreeMap<u32, Extremes>>,
+}
+
+impl RecordBook {
+    pub fn new(station: Option<StationId>) -> RecordBook {
+        RecordBook {
+            station,
+            all_time: Extremes::default(),
+            monthly: BTreeMap::new(),
+            daily: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the values of a record, returning the elements that set a new all time record.
+    pub fn add_record(&mut self, record: &Record) -> Vec<ExtremeElement> {
+        let mut records = Vec::new();
+        for (element, candidate) in candidates(record) {
+            let (month, day) = (candidate.date.month(), candidate.date.day());
+            self.monthly
+                .entry(month)
+                .or_default()
+                .add(element, &candidate);
+            self.daily
+                .entry(month)
+                .or_default()
+                .entry(day)
+                .or_default()
+                .add(element, &candidate);
+            if self.all_time.add(element, &candidate) && !records.contains(&element) {
+                records.push(element);
+            }
+        }
+        records
+    }
+
+    pub fn month(&self, month: u32) -> Option<&Extremes> {
+        self.monthly.get(&month)
+    }
+
+    pub fn day(&self, month: u32, day: u32) -> Option<&Extremes> {
+        self.daily.get(&month).and_then(|days| days.get(&day))
+    }
+}
+
+/// Record books of many stations, which records are routed to by their station.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RecordBooks {
+    books: BTreeMap<Option<StationId>, RecordBook>,
+}
+
+impl RecordBooks {
+    pub fn new() -> RecordBooks {
+        RecordBooks::default()
+    }
+
+    /// Adds a record to the book of its station, see `RecordBook::add_record`.
+    pub fn add_record(&mut self, record: &Record) -> Vec<ExtremeElement> {
+        let station = record.station().copied();
+        self.books
+            .entry(station)
+            .or_insert_with(|| RecordBook::new(station))
+            .add_record(record)
+    }
+
+    pub fn get(&self, station: Option<&StationId>) -> Option<&RecordBook> {
+        self.books.get(&station.copied())
+    }
+
+    /// The books ordered by station.
+    pub fn iter(&self) -> impl Iterator<Item = &RecordBook> {
+        self.books.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::records;
+
+    #[test]
+    fn test_record_book() {
+        let records = records(&[
+            &[("DATE", "2020-01-01T05:51:00"), ("OC1", "0150,1")],
+            &[
+                ("DATE", "2020-01-01T23:59:00"),
+                ("REPORT_TYPE", "SOD"),
+                ("AA1", "24,0123,9,1"),
+                ("KA1", "240,M,+0072,1"),
+                ("OE1", "1,24,02010,270,0530,1"),
+            ],
+            &[
+                ("DATE", "2020-02-03T11:51:00"),
+                ("TMP", "-0050,1"),
+                ("SLP", "09900,1"),
+            ],
+            &[
+                ("DATE", "2020-02-29T23:59:00"),
+                ("REPORT_TYPE", "SOM"),
+                ("MK1", "10300,150600,1,09850,031200,1"),
+            ],
+        ]);
+        let mut books = RecordBooks::new();
+        let new_records: Vec<Vec<ExtremeElement>> =
+            records.iter().map(|r| books.add_record(r)).collect();
+        assert_eq!(new_records[0].len(), 4);
+        // the SOD maximum beats the hourly temperature, and the peak wind the gust
+        assert_eq!(
+            new_records[1],
+            vec![
+                ExtremeElement::HighestTemperature,
+                ExtremeElement::HighestDailyPrecipitation,
+                ExtremeElement::StrongestGust
+            ]
+        );
+
+        let book = books.iter().next().unwrap();
+        let highest = book.all_time.highest_temperature.as_ref().unwrap();
+        assert_eq!((highest.value, highest.field), (7.2, "KAX"));
+        let gust = book.all_time.strongest_gust.as_ref().unwrap();
+        assert_eq!((gust.value, gust.field), (20.1, "OEX"));
+        let lowest = book.all_time.lowest_pressure.as_ref().unwrap();
+        assert_eq!((lowest.value, lowest.field), (985.0, "MK1"));
+        assert_eq!(lowest.date.to_string(), "2020-02-03 12:00:00");
+        assert_eq!(
+            book.day(2, 3)
+                .and_then(|d| d.lowest_pressure.as_ref())
+                .map(|p| p.field),
+            Some("MK1")
+        );
+        assert_eq!(
+            book.month(1)
+                .and_then(|m| m.lowest_temperature.as_ref())
+                .map(|t| t.value),
+            Some(6.1)
+        );
+        assert_eq!(
+            book.all_time.lowest_temperature.as_ref().map(|t| t.value),
+            Some(-5.0)
+        );
+    }
+
+    #[test]
+    fn test_flagged_values() {
+        let records = records(&[
+            // an erroneous 50 °C and a suspect sea level pressure
+            &[("TMP", "+0500,3"), ("SLP", "09500,2")],
+            &[("DATE", "2020-01-01T01:51:00"), ("SLP", "10050,1")],
+        ]);
+        let mut book = RecordBook::new(None);
+        let new_records: Vec<Vec<ExtremeElement>> =
+            records.iter().map(|r| book.add_record(r)).collect();
+        assert_eq!(new_records[0], vec![]);
+        let highest = book.all_time.highest_temperature.as_ref().unwrap();
+        assert_eq!(highest.value, 6.1);
+        assert_eq!(highest.date.to_string(), "2020-01-01 01:51:00");
+        let lowest = book.all_time.lowest_pressure.as_ref().unwrap();
+        assert_eq!(lowest.value, 1005.0);
+    }
+}
@@ -0,0 +1,35 @@
+use serde::Serialize;
+
+pub mod daily;
+pub mod design_conditions;
+pub mod extremes;
+pub mod monthly;
+pub mod normals;
+pub mod tmy;
+
+/// The mean of a set of observations and how many there were.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Mean {
+    pub value: f64,
+    pub count: usize,
+}
+
+impl Mean {
+    /// `None` when there are no values.
+    pub fn of<I>(values: I) -> Option<Mean>
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        let (sum, count) = values
+            .into_iter()
+            .fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+        if count == 0 {
+            None
+        } else {
+            Some(Mean {
+                value: sum / count as f64,
+                count,
+            })
+        }
+    }
+}
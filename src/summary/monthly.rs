@@ -0,0 +1,420 @@
+use crate::model::Record;
+use crate::report::{discrepancies, Discrepancy};
+use crate::station::id::StationId;
+use crate::summary::daily::DailySummary;
+use crate::summary::Mean;
+use crate::util::days_in_month;
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+
+/// The base temperature of heating and cooling degree days in °C (65 °F).
+pub const DEGREE_DAY_BASE: f64 = 18.3;
+
+/// Precipitation thresholds in mm of the `AE1` day counts: 0.01, 0.10, 0.50 and 1.00 inch.
+pub const PRECIPITATION_THRESHOLDS: [f64; 4] = [0.254, 2.54, 12.7, 25.4];
+
+// The `KE1` temperature thresholds: 32 °F, 90 °F and 0 °F.
+const FREEZING: f64 = 0.0;
+const HOT: f64 = 32.2;
+const VERY_COLD: f64 = -17.8;
+
+/// A value and the day it occurred on.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Extreme {
+    pub value: f64,
+    pub date: NaiveDate,
+}
+
+/// A summary of the daily summaries of one station over a month or a year.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PeriodSummary {
+    pub station: Option<StationId>,
+    pub year: i32,
+    /// `None` for an annual summary.
+    pub month: Option<u32>,
+    /// Days in the period.
+    pub days: u32,
+    /// Days with at least one hourly observation, see `DailySummary::hourly_observations`.
+    pub days_with_data: usize,
+    /// `days_with_data` as a fraction of `days`.
+    pub completeness: f64,
+    /// The mean of the daily mean, maximum and minimum temperatures in °C.
+    pub mean_temperature: Option<Mean>,
+    pub mean_max_temperature: Option<Mean>,
+    pub mean_min_temperature: Option<Mean>,
+    pub max_temperature: Option<Extreme>,
+    pub min_temperature: Option<Extreme>,
+    /// Degree days from the daily mean of the maximum and minimum, or the daily mean
+    /// temperature when either is missing.
+    pub heating_degree_days: Option<f64>,
+    pub cooling_degree_days: Option<f64>,
+    /// Total precipitation in mm over the days that have a precipitation total.
+    pub precipitation: Option<f64>,
+    pub precipitation_days: usize,
+    pub max_daily_precipitation: Option<Extreme>,
+    /// Days with precipitation of at least each of `PRECIPITATION_THRESHOLDS`.
+    pub days_with_precipitation: [usize; 4],
+    /// Days with a maximum at or below 32 °F, a maximum at or above 90 °F, a minimum at or
+    /// below 32 °F and a minimum at or below 0 °F, as in `KE1`.
+    pub max_temperature_freezing_days: usize,
+    pub max_temperature_hot_days: usize,
+    pub min_temperature_freezing_days: usize,
+    pub min_temperature_very_cold_days: usize,
+    /// The mean of the daily means in hPa.
+    pub sea_level_pressure: Option<Mean>,
+    pub station_pressure: Option<Mean>,
+    pub max_sea_level_pressure: Option<Extreme>,
+    pub min_sea_level_pressure: Option<Extreme>,
+}
+
+fn extreme<'a, F>(days: &[&'a DailySummary], value: F, highest: bool) -> Option<Extreme>
+where
+    F: Fn(&'a DailySummary) -> Option<f64>,
+{
+    days.iter()
+        .filter_map(|day| {
+            value(day).map(|value| Extreme {
+                value,
+                date: day.date,
+            })
+        })
+        .reduce(|a, b| {
+            if (b.value > a.value) == highest && b.value != a.value {
+                b
+            } else {
+                a
+            }
+        })
+}
+
+fn count<F>(days: &[&DailySummary], value: F) -> usize
+where
+    F: Fn(&DailySummary) -> bool,
+{
+    days.iter().filter(|day| value(day)).count()
+}
+
+impl PeriodSummary {
+    /// Summarizes the daily summaries of one station within a month, or within a year when
+    /// `month` is `None`.
+    pub fn from_days(
+        station: Option<StationId>,
+        year: i32,
+        month: Option<u32>,
+        days: &[&DailySummary],
+    ) -> PeriodSummary {
+        let days_in_period = match month {
+            Some(month) => days_in_month(year, month),
+            None => (1..=12).map(|m| days_in_month(year, m)).sum(),
+        };
+        let degree_day_temperatures: Vec<f64> = days
+            .iter()
+            .filter_map(|day| match (day.max_temperature, day.min_temperature) {
+                (Some(max), Some(min)) => Some((max + min) / 2.0),
+                _ => day.temperature.map(|t| t.value),
+            })
+            .collect();
+        let degree_days = |f: fn(f64) -> f64| {
+            if degree_day_temperatures.is_empty() {
+                None
+            } else {
+                Some(degree_day_temperatures.iter().map(|t| f(*t)).sum())
+            }
+        };
+        let precipitation = |day: &DailySummary| day.precipitation.map(|p| p.total);
+        let totals: Vec<f64> = days.iter().filter_map(|day| precipitation(day)).collect();
+        let days_with_data = days
+            .iter()
+            .filter(|day| day.hourly_observations > 0)
+            .count();
+
+        PeriodSummary {
+            station,
+            year,
+            month,
+            days: days_in_period,
+            days_with_data,
+            completeness: days_with_data as f64 / days_in_period as f64,
+            mean_temperature: Mean::of(days.iter().filter_map(|d| d.temperature.map(|t| t.value))),
+            mean_max_temperature: Mean::of(days.iter().filter_map(|d| d.max_temperature)),
+            mean_min_temperature: Mean::of(days.iter().filter_map(|d| d.min_temperature)),
+            max_temperature: extreme(days, |d| d.max_temperature, true),
+            min_temperature: extreme(days, |d| d.min_temperature, false),
+            heating_degree_days: degree_days(|t| (DEGREE_DAY_BASE - t).max(0.0)),
+            cooling_degree_days: degree_days(|t| (t - DEGREE_DAY_BASE).max(0.0)),
+            precipitation: if totals.is_empty() {
+                None
+            } else {
+                Some(totals.iter().sum())
+            },
+            precipitation_days: totals.len(),
+            max_daily_precipitation: extreme(days, precipitation, true),
+            days_with_precipitation: PRECIPITATION_THRESHOLDS
+                .map(|threshold| totals.iter().filter(|p| **p >= threshold).count()),
+            max_temperature_freezing_days: count(days, |d| {
+                d.max_temperature.is_some_and(|t| t <= FREEZING)
+            }),
+            max_temperature_hot_days: count(days, |d| d.max_temperature.is_some_and(|t| t >= HOT)),
+            min_temperature_freezing_days: count(days, |d| {
+                d.min_temperature.is_some_and(|t| t <= FREEZING)
+            }),
+            min_temperature_very_cold_days: count(days, |d| {
+                d.min_temperature.is_some_and(|t| t <= VERY_COLD)
+            }),
+            sea_level_pressure: Mean::of(
+                days.iter()
+                    .filter_map(|d| d.sea_level_pressure.map(|p| p.value)),
+            ),
+            station_pressure: Mean::of(
+                days.iter()
+                    .filter_map(|d| d.station_pressure.map(|p| p.value)),
+            ),
+            max_sea_level_pressure: extreme(days, |d| d.max_sea_level_pressure, true),
+            min_sea_level_pressure: extreme(days, |d| d.min_sea_level_pressure, false),
+        }
+    }
+
+    /// The differences between this monthly summary and the `AB1`, `AE1`, `KE1`, `MH1` and
+    /// `MK1` values reported in the station's records for the same month. Elements that are
+    /// not reported are not compared. Always empty for annual summaries.
+    pub fn compare<'a, I>(&self, records: I) -> Vec<Discrepancy>
+    where
+        I: IntoIterator<Item = &'a Record>,
+    {
+        let month = match self.month {
+            Some(month) => month,
+            None => return Vec::new(),
+        };
+        let records: Vec<&Record> = records
+            .into_iter()
+            .filter(|r| {
+                r.station() == self.station.as_ref()
+                    && r.date().year() == self.year
+                    && r.date().month() == month
+            })
+            .collect();
+        let reported = |f: &dyn Fn(&Record) -> Option<f64>| records.iter().find_map(|r| f(r));
+        let derived = |value: usize| Some(value as f64);
+        let day_of = |extreme: Option<Extreme>| extreme.map(|e| e.date.day() as f64);
+        // MK1 gives the date and time as DDHHMM
+        let reported_day = |date_time: Option<isize>| date_time.map(|d| (d / 10000) as f64);
+
+        let elements = [
+            (
+                "precipitation",
+                self.precipitation,
+                reported(&|r| r.ab1().and_then(|ab1| ab1.depth_dimension())),
+                0.1,
+            ),
+            (
+                "days_with_precipitation_0.01in",
+                derived(self.days_with_precipitation[0]),
+                reported(&|r| r.ae1().and_then(|ae1| ae1.days_01()).map(f64::from)),
+                0.0,
+            ),
+            (
+                "days_with_precipitation_0.10in",
+                derived(self.days_with_precipitation[1]),
+                reported(&|r| r.ae1().and_then(|ae1| ae1.days_10()).map(f64::from)),
+                0.0,
+            ),
+            (
+                "days_with_precipitation_0.50in",
+                derived(self.days_with_precipitation[2]),
+                reported(&|r| r.ae1().and_then(|ae1| ae1.days_50()).map(f64::from)),
+                0.0,
+            ),
+            (
+                "days_with_precipitation_1.00in",
+                derived(self.days_with_precipitation[3]),
+                reported(&|r| r.ae1().and_then(|ae1| ae1.days_100()).map(f64::from)),
+                0.0,
+            ),
+            (
+                "max_temperature_32f_days",
+                derived(self.max_temperature_freezing_days),
+                reported(&|r| {
+                    r.ke1()
+                        .and_then(|ke1| ke1.max_temp_32_f_days())
+                        .map(|d| d as f64)
+                }),
+                0.0,
+            ),
+            (
+                "max_temperature_90f_days",
+                derived(self.max_temperature_hot_days),
+                reported(&|r| {
+                    r.ke1()
+                        .and_then(|ke1| ke1.max_temp_90_f_days())
+                        .map(|d| d as f64)
+                }),
+                0.0,
+            ),
+            (
+                "min_temperature_32f_days",
+                derived(self.min_temperature_freezing_days),
+                reported(&|r| {
+                    r.ke1()
+                        .and_then(|ke1| ke1.min_temp_32_f_days())
+                        .map(|d| d as f64)
+                }),
+                0.0,
+            ),
+            (
+                "min_temperature_0f_days",
+                derived(self.min_temperature_very_cold_days),
+                reported(&|r| {
+                    r.ke1()
+                        .and_then(|ke1| ke1.min_temp_0_f_days())
+                        .map(|d| d as f64)
+                }),
+                0.0,
+            ),
+            (
+                "station_pressure",
+                self.station_pressure.map(|p| p.value),
+                reported(&|r| r.mh1().and_then(|mh1| mh1.avg_station_pressure_month())),
+                0.1,
+            ),
+            (
+                "sea_level_pressure",
+                self.sea_level_pressure.map(|p| p.value),
+                reported(&|r| r.mh1().and_then(|mh1| mh1.avg_sea_level_pressure_month())),
+                0.1,
+            ),
+            (
+                "max_sea_level_pressure",
+                self.max_sea_level_pressure.map(|p| p.value),
+                reported(&|r| r.mk1().and_then(|mk1| mk1.max_sea_level_pressure_month())),
+                0.1,
+            ),
+            (
+                "max_sea_level_pressure_day",
+                day_of(self.max_sea_level_pressure),
+                reported(&|r| {
+                    reported_day(
+                        r.mk1()
+                            .and_then(|mk1| mk1.max_sea_level_pressure_month_date_time()),
+                    )
+                }),
+                0.0,
+            ),
+            (
+                "min_sea_level_pressure",
+                self.min_sea_level_pressure.map(|p| p.value),
+                reported(&|r| r.mk1().and_then(|mk1| mk1.min_sea_level_pressure_month())),
+                0.1,
+            ),
+            (
+                "min_sea_level_pressure_day",
+                day_of(self.min_sea_level_pressure),
+                reported(&|r| {
+                    reported_day(
+                        r.mk1()
+                            .and_then(|mk1| mk1.min_sea_level_pressure_month_date_time()),
+                    )
+                }),
+                0.0,
+            ),
+        ];
+        discrepancies(
+            elements
+                .into_iter()
+                .filter(|(_, _, reported, _)| reported.is_some()),
+        )
+    }
+}
+
+fn group_days<K, F>(days: &[DailySummary], key: F) -> BTreeMap<K, Vec<&DailySummary>>
+where
+    K: Ord,
+    F: Fn(&DailySummary) -> K,
+{
+    let mut groups: BTreeMap<K, Vec<&DailySummary>> = BTreeMap::new();
+    for day in days {
+        groups.entry(key(day)).or_default().push(day);
+    }
+    groups
+}
+
+/// Monthly summaries by station, ordered by station and month.
+pub fn monthly_summaries(days: &[DailySummary]) -> Vec<PeriodSummary> {
+    group_days(days, |d| (d.station, d.date.year(), d.date.month()))
+        .into_iter()
+        .map(|((station, year, month), days)| {
+            PeriodSummary::from_days(station, year, Some(month), &days)
+        })
+        .collect()
+}
+
+/// Annual summaries by station, ordered by station and year.
+pub fn annual_summaries(days: &[DailySummary]) -> Vec<PeriodSummary> {
+    group_days(days, |d| (d.station, d.date.year()))
+        .into_iter()
+        .map(|((station, year), days)| PeriodSummary::from_days(station, year, None, &days))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summary::daily::daily_summaries;
+    use crate::util::testing::records;
+
+    #[test]
+    fn test_monthly_summary() {
+        let records = records(&[
+            &[
+                ("DATE", "2020-01-01T12:00:00"),
+                ("TMP", "+0100,1"),
+                ("AA1", "24,0030,9,1"),
+            ],
+            &[
+                ("DATE", "2020-01-01T18:00:00"),
+                ("TMP", "+0000,1"),
+                ("SLP", "10120,1"),
+            ],
+            &[
+                ("DATE", "2020-01-02T12:00:00"),
+                ("TMP", "-0050,1"),
+                ("SLP", "10080,1"),
+                ("AA1", "24,0000,2,1"),
+            ],
+            &[
+                ("DATE", "2020-01-31T23:59:00"),
+                ("REPORT_TYPE", "SOM"),
+                ("AB1", "00030,1,1"),
+                ("AE1", "01,1,01,1,00,1,00,1"),
+                ("KE1", "01,1,00,1,02,1,00,1"),
+                ("MH1", "99999,9,10100,1"),
+            ],
+        ]);
+        let days = daily_summaries(&records);
+        let months = monthly_summaries(&days);
+        assert_eq!(months.len(), 1);
+        let month = &months[0];
+        assert_eq!(month.days, 31);
+        assert_eq!(month.days_with_data, 2);
+        assert_eq!(month.max_temperature.unwrap().date.day(), 1);
+        assert_eq!(month.min_temperature.unwrap().value, -5.0);
+        // (10 + 0) / 2 = 5 and -5 give 13.3 + 23.3 heating degree days
+        assert!((month.heating_degree_days.unwrap() - 36.6).abs() < 1e-9);
+        assert_eq!(month.cooling_degree_days, Some(0.0));
+        assert_eq!(month.precipitation, Some(3.0));
+        assert_eq!(month.days_with_precipitation, [1, 1, 0, 0]);
+        assert_eq!(month.min_temperature_freezing_days, 2);
+
+        // the SOM record itself has no observations, and its MH1 mean is lower than the
+        // mean of the daily means
+        let discrepancies = month.compare(&records);
+        assert_eq!(discrepancies.len(), 1);
+        assert_eq!(discrepancies[0].element, "sea_level_pressure");
+
+        let years = annual_summaries(&days);
+        assert_eq!(years[0].days, 366);
+        assert!(years[0].compare(&records).is_empty());
+    }
+}
@@ -0,0 +1,372 @@
+use crate::station::id::StationId;
+use crate::summary::daily::DailySummary;
+use crate::summary::monthly::PRECIPITATION_THRESHOLDS;
+use crate::util::days_in_month;
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+use std::collections::BTreeMap;
+
+/// The period and completeness rules of normals.
+///
+/// A monthly value of a year is only used when no more than `max_missing_days` days are
+/// missing in total and no more than `max_consecutive_missing_days` in a row, the WMO 3/5
+/// rule by default. Monthly precipitation totals need every day. A normal is only computed
+/// when at least `min_years` of the years of the period have a value.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct NormalsRules {
+    pub start_year: i32,
+    pub end_year: i32,
+    pub max_missing_days: usize,
+    pub max_consecutive_missing_days: usize,
+    /// A fraction of the years of the period.
+    pub min_years: f64,
+    /// The width in days of the running mean that smooths the daily normals, 1 for none.
+    pub smoothing_days: usize,
+}
+
+impl Default for NormalsRules {
+    /// The 1991-2020 normals.
+    fn default() -> NormalsRules {
+        NormalsRules {
+            start_year: 1991,
+            end_year: 2020,
+            max_missing_days: 5,
+            max_consecutive_missing_days: 3,
+            min_years: 0.8,
+            smoothing_days: 15,
+        }
+    }
+}
+
+impl NormalsRules {
+    /// Whether a month with these daily values can be used.
+    pub fn is_complete(&self, values: &[Option<f64>]) -> bool {
+        let missing = values.iter().filter(|v| v.is_none()).count();
+        let consecutive = values
+            .split(|v| v.is_some())
+            .map(|run| run.len())
+            .max()
+            .unwrap_or(0);
+        missing <= self.max_missing_days && consecutive <= self.max_consecutive_missing_days
+    }
+
+    fn required_years(&self) -> usize {
+        let years = (self.end_year - self.start_year + 1).max(0) as f64;
+        (years * self.min_years).ceil() as usize
+    }
+
+    /// The mean of the values when there are enough of them.
+    fn normal(&self, values: &[f64]) -> Option<f64> {
+        if values.is_empty() || values.len() < self.required_years() {
+            None
+        } else {
+            Some(values.iter().sum::<f64>() / values.len() as f64)
+        }
+    }
+}
+
+/// The normals of a station for a calendar month.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MonthlyNormal {
+    pub station: Option<StationId>,
+    pub month: u32,
+    /// Mean temperatures in °C, from the monthly means of the daily means, maximums and
+    /// minimums.
+    pub mean_temperature: Option<f64>,
+    pub mean_max_temperature: Option<f64>,
+    pub mean_min_temperature: Option<f64>,
+    /// The years the mean temperature normal is made of.
+    pub temperature_years: usize,
+    /// Mean monthly precipitation in mm.
+    pub precipitation: Option<f64>,
+    /// Mean number of days with precipitation of at least 0.01, 0.10, 0.50 and 1.00 inch.
+    pub days_01: Option<f64>,
+    pub days_10: Option<f64>,
+    pub days_50: Option<f64>,
+    pub days_100: Option<f64>,
+    pub precipitation_years: usize,
+}
+
+/// The normals of a station for a calendar day, smoothed with a running mean.
+#[skip_serializing_none]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DailyNormal {
+    pub station: Option<StationId>,
+    pub month: u32,
+    pub day: u32,
+    pub mean_temperature: Option<f64>,
+    pub mean_max_temperature: Option<f64>,
+    pub mean_min_temperature: Option<f64>,
+    /// Mean daily precipitation in mm.
+    pub precipitation: Option<f64>,
+    /// The fraction of days with at least 0.01 inch of precipitation.
+    pub precipitation_frequency: Option<f64>,
+}
+
+/// The normals of one station over the period of the rules.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Normals {
+    pub station: Option<StationId>,
+    pub start_year: i32,
+    pub end_year: i32,
+    pub monthly: Vec<MonthlyNormal>,
+    /// The 365 days of a common year, February 29 is left out.
+    pub daily: Vec<DailyNormal>,
+}
+
+type Element = fn(&DailySummary) -> Option<f64>;
+
+const MEAN: Element = |d| d.temperature.map(|t| t.value);
+const MAX: Element = |d| d.max_temperature;
+const MIN: Element = |d| d.min_temperature;
+const PRECIPITATION: Element = |d| d.precipitation.map(|p| p.total);
+
+/// A centered running mean over the days of the year, wrapping around the year end. Days
+/// without a value stay without one.
+fn smooth(values: &[Option<f64>], width: usize) -> Vec<Option<f64>> {
+    let half = (width.max(1) / 2) as isize;
+    let n = values.len() as isize;
+    (0..n)
+        .map(|i| {
+            values[i as usize]?;
+            let window: Vec<f64> = (i - half..=i + half)
+                .filter_map(|j| values[j.rem_euclid(n) as usize])
+                .collect();
+            Some(window.iter().sum::<f64>() / window.len() as f64)
+        })
+        .collect()
+}
+
+impl Normals {
+    /// Computes the normals of one station from its daily summaries.
+    pub fn from_days(
+        station: Option<StationId>,
+        days: &[&DailySummary],
+        rules: &NormalsRules,
+    ) -> Normals {
+        let by_date: BTreeMap<NaiveDate, &DailySummary> =
+            days.iter().map(|d| (d.date, *d)).collect();
+        let years = rules.start_year..=rules.end_year;
+        let value = |date: Option<NaiveDate>, element: Element| {
+            date.and_then(|date| by_date.get(&date))
+                .and_then(|d| element(d))
+        };
+
+        let monthly = (1..=12)
+            .map(|month| {
+                let month_values = |year: i32, element: Element| -> Vec<Option<f64>> {
+                    (1..=days_in_month(year, month))
+                        .map(|day| value(NaiveDate::from_ymd_opt(year, month, day), element))
+                        .collect()
+                };
+                let monthly_means = |element: Element| -> Vec<f64> {
+                    years
+                        .clone()
+                        .map(|year| month_values(year, element))
+                        .filter(|values| rules.is_complete(values))
+                        .map(|values| values.into_iter().flatten().collect::<Vec<f64>>())
+                        .filter(|values| !values.is_empty())
+                        .map(|values| values.iter().sum::<f64>() / values.len() as f64)
+                        .collect()
+                };
+                let means = monthly_means(MEAN);
+                let precipitation: Vec<Vec<f64>> = years
+                    .clone()
+                    .map(|year| month_values(year, PRECIPITATION))
+                    .filter(|values| values.iter().all(Option::is_some))
+                    .map(|values| values.into_iter().flatten().collect())
+                    .collect();
+                let days_with = |threshold: f64| {
+                    rules.normal(
+                        &precipitation
+                            .iter()
+                            .map(|p| p.iter().filter(|p| **p >= threshold).count() as f64)
+                            .collect::<Vec<_>>(),
+                    )
+                };
+                MonthlyNormal {
+                    station,
+                    month,
+                    mean_temperature: rules.normal(&means),
+                    mean_max_temperature: rules.normal(&monthly_means(MAX)),
+                    mean_min_temperature: rules.normal(&monthly_means(MIN)),
+                    temperature_years: means.len(),
+                    precipitation: rules.normal(
+                        &precipitation
+                            .iter()
+                            .map(|p| p.iter().sum())
+                            .collect::<Vec<_>>(),
+                    ),
+                    days_01: days_with(PRECIPITATION_THRESHOLDS[0]),
+                    days_10: days_with(PRECIPITATION_THRESHOLDS[1]),
+                    days_50: days_with(PRECIPITATION_THRESHOLDS[2]),
+                    days_100: days_with(PRECIPITATION_THRESHOLDS[3]),
+                    precipitation_years: precipitation.len(),
+                }
+            })
+            .collect();
+
+        // 2001 is a common year
+        let calendar: Vec<NaiveDate> = NaiveDate::from_ymd_opt(2001, 1, 1)
+            .map(|start| start.iter_days().take(365).collect())
+            .unwrap_or_default();
+        let daily_values = |element: Element| -> Vec<Vec<f64>> {
+            calendar
+                .iter()
+                .map(|date| {
+                    years
+                        .clone()
+                        .filter_map(|year| value(date.with_year(year), element))
+                        .collect()
+                })
+                .collect()
+        };
+        let daily_normals = |element: Element| {
+            let raw: Vec<Option<f64>> = daily_values(element)
+                .iter()
+                .map(|values| rules.normal(values))
+                .collect();
+            smooth(&raw, rules.smoothing_days)
+        };
+        let (means, maxes, mins) = (daily_normals(MEAN), daily_normals(MAX), daily_normals(MIN));
+        let precipitation = daily_values(PRECIPITATION);
+        let frequency: Vec<Option<f64>> = precipitation
+            .iter()
+            .map(|values| {
+                let wet: Vec<f64> = values
+                    .iter()
+                    .map(|p| f64::from(u8::from(*p >= PRECIPITATION_THRESHOLDS[0])))
+                    .collect();
+                rules.normal(&wet)
+            })
+            .collect();
+        let precipitation = smooth(
+            &precipitation
+                .iter()
+                .map(|values| rules.normal(values))
+                .collect::<Vec<_>>(),
+            rules.smoothing_days,
+        );
+        let frequency = smooth(&frequency, rules.smoothing_days);
+
+        let daily = calendar
+            .iter()
+            .enumerate()
+            .map(|(i, date)| DailyNormal {
+                station,
+                month: date.month(),
+                day: date.day(),
+                mean_temperature: means[i],
+                mean_max_temperature: maxes[i],
+                mean_min_temperature: mins[i],
+                precipitation: precipitation[i],
+                precipitation_frequency: frequency[i],
+            })
+            .collect();
+
+        Normals {
+            station,
+            start_year: rules.start_year,
+            end_year: rules.end_year,
+            monthly,
+            daily,
+        }
+    }
+}
+
+/// The normals of each station in the daily summaries, ordered by station.
+pub fn normals(days: &[DailySummary], rules: &NormalsRules) -> Vec<Normals> {
+    let mut stations: BTreeMap<Option<StationId>, Vec<&DailySummary>> = BTreeMap::new();
+    for day in days {
+        stations.entry(day.station).or_default().push(day);
+    }
+    stations
+        .into_iter()
+        .map(|(station, days)| Normals::from_days(station, &days, rules))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summary::daily::{DailyPrecipitation, Frshtt};
+    use crate::summary::Mean;
+
+    fn day(date: NaiveDate, temperature: f64, precipitation: f64) -> DailySummary {
+        DailySummary {
+            station: None,
+            date,
+            observations: 24,
+            hourly_observations: 24,
+            temperature: Some(Mean {
+                value: temperature,
+                count: 24,
+            }),
+            max_temperature: Some(temperature + 5.0),
+            min_temperature: Some(temperature - 5.0),
+            dew_point: None,
+            sea_level_pressure: None,
+            max_sea_level_pressure: None,
+            min_sea_level_pressure: None,
+            station_pressure: None,
+            visibility: None,
+            wind_speed: None,
+            max_wind_speed: None,
+            max_gust: None,
+            precipitation: Some(DailyPrecipitation {
+                total: precipitation,
+                trace: false,
+                period: 24,
+                hours: 24,
+            }),
+            snow_depth: None,
+            frshtt: Frshtt::default(),
+        }
+    }
+
+    #[test]
+    fn test_normals() {
+        let mut days = Vec::new();
+        for year in 2001..=2003 {
+            let start = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+            for date in start.iter_days().take_while(|d| d.year() == year) {
+                // January 2001 misses 4 days in a row, and 2003 is 3 °C warmer
+                if year == 2001 && date.month() == 1 && (10..14).contains(&date.day()) {
+                    continue;
+                }
+                let temperature = if year == 2003 { 13.0 } else { 10.0 };
+                let precipitation = if date.day() % 10 == 0 { 5.0 } else { 0.0 };
+                days.push(day(date, temperature, precipitation));
+            }
+        }
+        let rules = NormalsRules {
+            start_year: 2001,
+            end_year: 2003,
+            min_years: 0.6,
+            ..NormalsRules::default()
+        };
+        let normals = normals(&days, &rules);
+        assert_eq!(normals.len(), 1);
+        let january = &normals[0].monthly[0];
+        assert_eq!(january.temperature_years, 2);
+        assert!((january.mean_temperature.unwrap() - 11.5).abs() < 1e-9);
+        assert!((january.mean_max_temperature.unwrap() - 16.5).abs() < 1e-9);
+        assert_eq!(january.precipitation_years, 2);
+        assert_eq!(january.precipitation, Some(15.0));
+        assert_eq!(january.days_10, Some(3.0));
+        assert_eq!(january.days_100, Some(0.0));
+        let february = &normals[0].monthly[1];
+        assert_eq!(february.temperature_years, 3);
+        assert!((february.mean_temperature.unwrap() - 11.0).abs() < 1e-9);
+
+        let daily = &normals[0].daily;
+        assert_eq!(daily.len(), 365);
+        assert_eq!((daily[59].month, daily[59].day), (3, 1));
+        assert!((daily[100].mean_temperature.unwrap() - 11.0).abs() < 1e-9);
+        // one wet day in ten, smoothed over 15 days
+        let frequency = daily[100].precipitation_frequency.unwrap();
+        assert!(frequency > 0.0 && frequency < 0.2);
+    }
+}
@@ -0,0 +1,565 @@
+use crate::model::Record;
+use crate::series::resample::{
+    resample, Element, HourlyObservation, HourlyValue, Provenance, ResamplePriority,
+};
+use crate::station::id::StationId;
+use crate::summary::Mean;
+use crate::util::days_in_month;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The elements blended across the joins between months of different years.
+const SMOOTHED_ELEMENTS: [Element; 5] = [
+    Element::Temperature,
+    Element::DewPoint,
+    Element::SeaLevelPressure,
+    Element::StationPressure,
+    Element::WindSpeed,
+];
+
+/// A daily index compared between a month and the long term.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TmyIndex {
+    MaxDryBulb,
+    MinDryBulb,
+    MeanDryBulb,
+    MaxDewPoint,
+    MinDewPoint,
+    MeanDewPoint,
+    MaxWindSpeed,
+    MeanWindSpeed,
+    /// The daily total of global horizontal irradiance in Wh/m².
+    GlobalIrradiance,
+    /// The daily total of direct normal irradiance in Wh/m².
+    DirectIrradiance,
+}
+
+impl TmyIndex {
+    pub const ALL: [TmyIndex; 10] = [
+        TmyIndex::MaxDryBulb,
+        TmyIndex::MinDryBulb,
+        TmyIndex::MeanDryBulb,
+        TmyIndex::MaxDewPoint,
+        TmyIndex::MinDewPoint,
+        TmyIndex::MeanDewPoint,
+        TmyIndex::MaxWindSpeed,
+        TmyIndex::MeanWindSpeed,
+        TmyIndex::GlobalIrradiance,
+        TmyIndex::DirectIrradiance,
+    ];
+
+    /// The weight of the index in TMY3.
+    pub fn tmy3_weight(self) -> f64 {
+        match self {
+            TmyIndex::MeanDryBulb | TmyIndex::MeanDewPoint => 2.0 / 20.0,
+            TmyIndex::GlobalIrradiance | TmyIndex::DirectIrradiance => 5.0 / 20.0,
+            _ => 1.0 / 20.0,
+        }
+    }
+}
+
+/// How typical months are selected.
+///
+/// A day has an index when at least `min_day_hours` of its hours have the element, and a
+/// month of a year is a candidate when at least `min_days` of its days have every index
+/// used. Indices without any value for a calendar month are left out for that month and
+/// the other weights scaled up to make up for them.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TmyRules {
+    pub weights: Vec<(TmyIndex, f64)>,
+    pub min_day_hours: usize,
+    /// A fraction of the days of the month.
+    pub min_days: f64,
+    /// How many of the candidates with the lowest weighted sums are ranked by their means,
+    /// medians and persistence.
+    pub candidates: usize,
+    /// The hours on each side of a join between months of different years that are blended.
+    pub smoothing_hours: i64,
+}
+
+impl Default for TmyRules {
+    /// The TMY3 weights and selection.
+    fn default() -> TmyRules {
+        TmyRules {
+            weights: TmyIndex::ALL
+                .iter()
+                .map(|i| (*i, i.tmy3_weight()))
+                .collect(),
+            min_day_hours: 20,
+            min_days: 0.8,
+            candidates: 5,
+            smoothing_hours: 6,
+        }
+    }
+}
+
+/// The month of a year selected for a calendar month.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TypicalMonth {
+    pub month: u32,
+    pub year: i32,
+    /// The weighted sum of the Finkelstein-Schafer statistics of the month.
+    pub weighted_sum: f64,
+    /// The Finkelstein-Schafer statistic of each index used.
+    pub statistics: Vec<(TmyIndex, f64)>,
+    /// The years that had enough data to be candidates.
+    pub candidate_years: Vec<i32>,
+}
+
+/// A typical meteorological year of a station.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TypicalYear {
+    pub station: Option<StationId>,
+    pub months: Vec<TypicalMonth>,
+    /// The 8760 hours of the year from January to December without February 29, each
+    /// keeping the date of the year it was taken from.
+    pub hours: Vec<HourlyObservation>,
+}
+
+/// The Finkelstein-Schafer statistic of each index.
+type Statistics = Vec<(TmyIndex, f64)>;
+
+/// The daily indices of a day.
+struct Day {
+    date: NaiveDate,
+    indices: BTreeMap<TmyIndex, f64>,
+}
+
+impl Day {
+    fn from_hours(date: NaiveDate, hours: &[&HourlyObservation], rules: &TmyRules) -> Day {
+        let values = |element: Element| -> Option<Vec<f64>> {
+            let values: Vec<f64> = hours.iter().filter_map(|h| h.value(element)).collect();
+            (values.len() >= rules.min_day_hours).then_some(values)
+        };
+        let max = |values: &[f64]| values.iter().copied().fold(f64::MIN, f64::max);
+        let min = |values: &[f64]| values.iter().copied().fold(f64::MAX, f64::min);
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+
+        let mut indices = BTreeMap::new();
+        if let Some(t) = values(Element::Temperature) {
+            indices.insert(TmyIndex::MaxDryBulb, max(&t));
+            indices.insert(TmyIndex::MinDryBulb, min(&t));
+            indices.insert(TmyIndex::MeanDryBulb, mean(&t));
+        }
+        if let Some(d) = values(Element::DewPoint) {
+            indices.insert(TmyIndex::MaxDewPoint, max(&d));
+            indices.insert(TmyIndex::MinDewPoint, min(&d));
+            indices.insert(TmyIndex::MeanDewPoint, mean(&d));
+        }
+        if let Some(w) = values(Element::WindSpeed) {
+            indices.insert(TmyIndex::MaxWindSpeed, max(&w));
+            indices.insert(TmyIndex::MeanWindSpeed, mean(&w));
+        }
+        // missing hours count as the mean of the others so that totals stay comparable
+        if let Some(g) = values(Element::GlobalIrradiance) {
+            indices.insert(TmyIndex::GlobalIrradiance, mean(&g) * 24.0);
+        }
+        if let Some(d) = values(Element::DirectIrradiance) {
+            indices.insert(TmyIndex::DirectIrradiance, mean(&d) * 24.0);
+        }
+        Day { date, indices }
+    }
+}
+
+fn sorted(values: impl Iterator<Item = f64>) -> Vec<f64> {
+    let mut values: Vec<f64> = values.collect();
+    values.sort_by(f64::total_cmp);
+    values
+}
+
+/// The fraction of the sorted values at or below `x`.
+fn cdf(sorted: &[f64], x: f64) -> f64 {
+    sorted.partition_point(|v| *v <= x) as f64 / sorted.len() as f64
+}
+
+/// The Finkelstein-Schafer statistic, the mean absolute difference between the cumulative
+/// distributions of a month and of the long term at the values of the month.
+fn finkelstein_schafer(month: &[f64], long_term: &[f64]) -> f64 {
+    month
+        .iter()
+        .map(|x| (cdf(month, *x) - cdf(long_term, *x)).abs())
+        .sum::<f64>()
+        / month.len() as f64
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+/// The number of runs of consecutive days meeting a condition and the longest of them.
+fn runs(days: &[&Day], condition: impl Fn(&Day) -> bool) -> (usize, usize) {
+    let (mut count, mut longest, mut current) = (0, 0, 0);
+    let mut previous: Option<NaiveDate> = None;
+    for day in days {
+        let consecutive = previous.and_then(|p| p.succ_opt()) == Some(day.date);
+        if condition(day) {
+            current = if consecutive { current + 1 } else { 1 };
+            if current == 1 {
+                count += 1;
+            }
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+        previous = Some(day.date);
+    }
+    (count, longest)
+}
+
+/// Selects the typical month among the years of one calendar month.
+///
+/// As in TMY3, the candidates with the lowest weighted sums are ranked by how far their
+/// mean and median daily mean dry-bulb and global irradiance are from the long term, in
+/// long term standard deviations. The first ranked is taken unless it alone has the longest
+/// run or the most runs, or it has no runs, of warm, cold or dull days: the days above the
+/// 67th or below the 33rd percentile of daily mean dry-bulb and below the 33rd percentile
+/// of global irradiance. When every candidate fails, the first ranked is taken anyway.
+fn select_month(
+    month: u32,
+    years: &BTreeMap<i32, Vec<&Day>>,
+    rules: &TmyRules,
+) -> Option<TypicalMonth> {
+    let long_term = |index: TmyIndex| {
+        sorted(
+            years
+                .values()
+                .flatten()
+                .filter_map(move |d| d.indices.get(&index).copied()),
+        )
+    };
+    let weights: Vec<(TmyIndex, f64, Vec<f64>)> = rules
+        .weights
+        .iter()
+        .filter(|(_, weight)| *weight > 0.0)
+        .map(|(index, weight)| (*index, *weight, long_term(*index)))
+        .filter(|(_, _, values)| !values.is_empty())
+        .collect();
+    if weights.is_empty() {
+        return None;
+    }
+    let total_weight: f64 = weights.iter().map(|(_, weight, _)| weight).sum();
+
+    let mut candidates: Vec<(i32, f64, Statistics)> = Vec::new();
+    for (year, days) in years {
+        let complete: Vec<&&Day> = days
+            .iter()
+            .filter(|d| weights.iter().all(|(i, _, _)| d.indices.contains_key(i)))
+            .collect();
+        let days_in_month = days_in_month(*year, month) as f64;
+        if (complete.len() as f64) < (days_in_month * rules.min_days).ceil() {
+            continue;
+        }
+        let statistics: Statistics = weights
+            .iter()
+            .map(|(index, _, long_term)| {
+                let values = sorted(complete.iter().map(|d| d.indices[index]));
+                (*index, finkelstein_schafer(&values, long_term))
+            })
+            .collect();
+        let weighted_sum = weights
+            .iter()
+            .zip(&statistics)
+            .map(|((_, weight, _), (_, fs))| weight / total_weight * fs)
+            .sum();
+        candidates.push((*year, weighted_sum, statistics));
+    }
+    let candidate_years: Vec<i32> = candidates.iter().map(|c| c.0).collect();
+    candidates.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+    candidates.truncate(rules.candidates.max(1));
+
+    // the largest deviation of the mean and median from the long term
+    let deviation = |year: i32| {
+        [TmyIndex::MeanDryBulb, TmyIndex::GlobalIrradiance]
+            .into_iter()
+            .filter_map(|index| {
+                let long_term = &weights.iter().find(|(i, _, _)| *i == index)?.2;
+                let values = sorted(
+                    years[&year]
+                        .iter()
+                        .filter_map(|d| d.indices.get(&index).copied()),
+                );
+                let mean = Mean::of(values.iter().copied())?.value;
+                let long_term_mean = Mean::of(long_term.iter().copied())?.value;
+                let spread = (long_term
+                    .iter()
+                    .map(|v| (v - long_term_mean).powi(2))
+                    .sum::<f64>()
+                    / long_term.len() as f64)
+                    .sqrt();
+                // a constant index ranks nothing
+                if spread < 1e-6 {
+                    return None;
+                }
+                Some(
+                    ((mean - long_term_mean).abs() / spread)
+                        .max((median(&values) - median(long_term)).abs() / spread),
+                )
+            })
+            .fold(0.0, f64::max)
+    };
+    candidates.sort_by(|a, b| {
+        deviation(a.0)
+            .total_cmp(&deviation(b.0))
+            .then(a.0.cmp(&b.0))
+    });
+
+    let mut excluded = vec![false; candidates.len()];
+    let percentile = |index: TmyIndex, percent: f64| {
+        let values = long_term(index);
+        (!values.is_empty()).then(|| values[((values.len() - 1) as f64 * percent) as usize])
+    };
+    let conditions: Vec<(TmyIndex, f64, bool)> = [
+        (TmyIndex::MeanDryBulb, 0.67, true),
+        (TmyIndex::MeanDryBulb, 0.33, false),
+        (TmyIndex::GlobalIrradiance, 0.33, false),
+    ]
+    .into_iter()
+    .filter_map(|(index, percent, above)| Some((index, percentile(index, percent)?, above)))
+    .collect();
+    for (index, threshold, above) in conditions {
+        let candidate_runs: Vec<(usize, usize)> = candidates
+            .iter()
+            .map(|(year, _, _)| {
+                runs(&years[year], |d| {
+                    d.indices.get(&index).is_some_and(|v| {
+                        if above {
+                            *v > threshold
+                        } else {
+                            *v < threshold
+                        }
+                    })
+                })
+            })
+            .collect();
+        // a maximum shared by several candidates doesn't single any of them out
+        let unique_max = |values: Vec<usize>| {
+            let max = values.iter().max().copied().unwrap_or(0);
+            (values.iter().filter(|v| **v == max).count() == 1).then_some(max)
+        };
+        let most = unique_max(candidate_runs.iter().map(|r| r.0).collect());
+        let longest = unique_max(candidate_runs.iter().map(|r| r.1).collect());
+        let any_runs = candidate_runs.iter().any(|(count, _)| *count > 0);
+        for (excluded, (count, length)) in excluded.iter_mut().zip(candidate_runs) {
+            *excluded |= (any_runs && count == 0) || Some(count) == most || Some(length) == longest;
+        }
+    }
+    let selected = candidates
+        .iter()
+        .zip(&excluded)
+        .find(|(_, excluded)| !**excluded)
+        .map(|(candidate, _)| candidate)
+        .or(candidates.first())?;
+
+    Some(TypicalMonth {
+        month,
+        year: selected.0,
+        weighted_sum: selected.1,
+        statistics: selected.2.clone(),
+        candidate_years,
+    })
+}
+
+/// The hours of a month of a year, without February 29.
+fn month_hours(year: i32, month: u32) -> impl Iterator<Item = NaiveDateTime> {
+    let start = NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("valid month");
+    let days = if month == 2 {
+        28
+    } else {
+        days_in_month(year, month)
+    };
+    (0..days as i64 * 24).map(move |h| start + Duration::hours(h))
+}
+
+/// Blends the elements linearly across the hours on each side of the join at `join`, the
+/// first hour of the later month.
+fn smooth(hours: &mut [HourlyObservation], join: usize, width: i64) {
+    let width = width as usize;
+    if width == 0 || join < width || join + width > hours.len() {
+        return;
+    }
+    let (first, last) = (join - width, join + width - 1);
+    for element in SMOOTHED_ELEMENTS {
+        let (Some(a), Some(b)) = (hours[first].value(element), hours[last].value(element)) else {
+            continue;
+        };
+        for (i, observation) in hours[first + 1..last].iter_mut().enumerate() {
+            let fraction = (i + 1) as f64 / (last - first) as f64;
+            let hour = observation.hour;
+            *observation.get_mut(element) = Some(HourlyValue {
+                value: a + (b - a) * fraction,
+                date: hour,
+                report_type: None,
+                provenance: Provenance::Linear,
+            });
+        }
+    }
+}
+
+impl TypicalYear {
+    /// Builds the typical year of one station from its hourly observations, such as those of
+    /// `series::resample::resample`, ideally with their gaps filled. `None` when a calendar
+    /// month has no candidate.
+    ///
+    /// The hours of the selected months are copied as they are, hours without an observation
+    /// having no elements, and the joins between months of different years are blended over
+    /// `smoothing_hours` on each side.
+    pub fn from_observations(
+        observations: &[HourlyObservation],
+        rules: &TmyRules,
+    ) -> Option<TypicalYear> {
+        let mut by_date: BTreeMap<NaiveDate, Vec<&HourlyObservation>> = BTreeMap::new();
+        for observation in observations {
+            by_date
+                .entry(observation.hour.date())
+                .or_default()
+                .push(observation);
+        }
+        let days: Vec<Day> = by_date
+            .iter()
+            .map(|(date, hours)| Day::from_hours(*date, hours, rules))
+            .collect();
+        let mut months: BTreeMap<u32, BTreeMap<i32, Vec<&Day>>> = BTreeMap::new();
+        for day in &days {
+            months
+                .entry(day.date.month())
+                .or_default()
+                .entry(day.date.year())
+                .or_default()
+                .push(day);
+        }
+
+        let months = (1..=12)
+            .map(|month| select_month(month, months.get(&month)?, rules))
+            .collect::<Option<Vec<TypicalMonth>>>()?;
+
+        let by_hour: BTreeMap<NaiveDateTime, &HourlyObservation> =
+            observations.iter().map(|o| (o.hour, o)).collect();
+        let station = observations.first().and_then(|o| o.station);
+        let mut hours: Vec<HourlyObservation> = Vec::with_capacity(8760);
+        for (i, month) in months.iter().enumerate() {
+            let join = hours.len();
+            hours.extend(month_hours(month.year, month.month).map(|hour| {
+                by_hour.get(&hour).map_or_else(
+                    || HourlyObservation::from_reports(station, hour, &[]),
+                    |o| (*o).clone(),
+                )
+            }));
+            if i > 0 && months[i - 1].year != month.year {
+                smooth(&mut hours, join, rules.smoothing_hours);
+            }
+        }
+        Some(TypicalYear {
+            station,
+            months,
+            hours,
+        })
+    }
+}
+
+/// The typical year of each station that has one, from the records resampled to hours with
+/// the default `ResamplePriority`, ordered by station.
+pub fn typical_years(records: &[Record], rules: &TmyRules) -> Vec<TypicalYear> {
+    resample(records, &ResamplePriority::default())
+        .chunk_by(|a, b| a.station == b.station)
+        .filter_map(|observations| TypicalYear::from_observations(observations, rules))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    #[test]
+    fn test_typical_year() {
+        let start = NaiveDate::from_ymd_opt(2018, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let end = NaiveDate::from_ymd_opt(2021, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        // 2018 is 3 °C warmer than 2019 and 2020 3 °C colder, except for a July 2019 6 °C
+        // warmer, which leaves 2018 in the middle for July
+        let observations: Vec<HourlyObservation> = (0..(end - start).num_hours())
+            .map(|h| {
+                let hour = start + Duration::hours(h);
+                let season = -(2.0 * std::f64::consts::PI * hour.ordinal0() as f64 / 365.0).cos();
+                let day = -(2.0 * std::f64::consts::PI * hour.hour() as f64 / 24.0).cos();
+                let offset = match (hour.year(), hour.month()) {
+                    (2019, 7) => 6.0,
+                    (2018, _) => 3.0,
+                    (2020, _) => -3.0,
+                    _ => 0.0,
+                };
+                let temperature = 10.0 + 15.0 * season + 5.0 * day + offset;
+                let mut observation = HourlyObservation::from_reports(None, hour, &[]);
+                for (element, value) in [
+                    (Element::Temperature, temperature),
+                    (Element::DewPoint, temperature - 5.0),
+                    (Element::WindSpeed, 3.0),
+                    (Element::GlobalIrradiance, (-day * 800.0).max(0.0)),
+                ] {
+                    *observation.get_mut(element) = Some(HourlyValue {
+                        value,
+                        date: hour,
+                        report_type: None,
+                        provenance: Provenance::Reported,
+                    });
+                }
+                observation
+            })
+            .collect();
+
+        let year = TypicalYear::from_observations(&observations, &TmyRules::default()).unwrap();
+        assert_eq!(year.hours.len(), 8760);
+        let years: Vec<i32> = year.months.iter().map(|m| m.year).collect();
+        let mut expected = vec![2019; 12];
+        expected[6] = 2018;
+        assert_eq!(years, expected);
+        assert_eq!(year.months[0].candidate_years, vec![2018, 2019, 2020]);
+        // no direct irradiance, so the weights of the other indices are scaled up
+        assert_eq!(year.months[0].statistics.len(), 9);
+
+        // July is taken from 2018 and blended with the 2019 months around it
+        let july = (31 + 28 + 31 + 30 + 31 + 30) * 24;
+        assert_eq!(year.hours[july].hour.to_string(), "2018-07-01 00:00:00");
+        assert_eq!(
+            year.hours[july].temperature.as_ref().unwrap().provenance,
+            Provenance::Linear
+        );
+        assert_eq!(
+            year.hours[july + 6]
+                .temperature
+                .as_ref()
+                .unwrap()
+                .provenance,
+            Provenance::Reported
+        );
+        assert_eq!(
+            year.hours[july - 6]
+                .temperature
+                .as_ref()
+                .unwrap()
+                .provenance,
+            Provenance::Reported
+        );
+        assert_eq!(
+            year.hours[july - 5]
+                .global_irradiance
+                .as_ref()
+                .unwrap()
+                .provenance,
+            Provenance::Reported
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use chrono::NaiveDateTime;
+#[cfg(test)]
+pub(crate) mod testing;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
 use serde::{de, Deserialize, Deserializer,  Serializer};
 
 
@@ -31,6 +34,14 @@ where
     serializer.serialize_str(&x.format("%Y-%m-%dT%T").to_string())
 }
 
+pub fn naive_date_from_str<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+    NaiveDate::parse_from_str(&s, "%Y%m%d").map_err(de::Error::custom)
+}
+
 pub fn remove_whitespace<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
@@ -58,6 +69,20 @@ pub fn parse_null (s: &str) -> Option<String> {
     }
 }
 
+/// The (year, month) pairs spanned by the inclusive range `start..=end`.
+pub fn months_between(start: NaiveDate, end: NaiveDate) -> impl Iterator<Item = (i32, u32)> {
+    let first = start.year() * 12 + start.month0() as i32;
+    let last = end.year() * 12 + end.month0() as i32;
+    (first..=last).map(|m| (m.div_euclid(12), m.rem_euclid(12) as u32 + 1))
+}
+
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(0)
+}
 
 #[cfg(test)]
 mod tests {
@@ -77,4 +102,15 @@ mod tests {
         // check a string with a single non-9
         assert_eq!(super::is_null("929"), false);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_months_between() {
+        use chrono::NaiveDate;
+        let start = NaiveDate::from_ymd_opt(2019, 11, 15).unwrap();
+        let end = NaiveDate::from_ymd_opt(2020, 2, 1).unwrap();
+        let months: Vec<_> = super::months_between(start, end).collect();
+        assert_eq!(months, vec![(2019, 11), (2019, 12), (2020, 1), (2020, 2)]);
+        assert_eq!(super::days_in_month(2020, 2), 29);
+        assert_eq!(super::days_in_month(2019, 12), 31);
+    }
+}
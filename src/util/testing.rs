@@ -0,0 +1,74 @@
+use crate::model::Record;
+
+/// The columns every record carries, with the values of an ordinary LaGuardia routine
+/// observation.
+const DEFAULTS: [(&str, &str); 16] = [
+    ("STATION", "72503014732"),
+    ("DATE", "2020-01-01T00:51:00"),
+    ("SOURCE", "7"),
+    ("LATITUDE", "40.77"),
+    ("LONGITUDE", "-73.88"),
+    ("ELEVATION", "3.4"),
+    ("NAME", "LAGUARDIA"),
+    ("REPORT_TYPE", "FM-15"),
+    ("CALL_SIGN", "99999"),
+    ("QUALITY_CONTROL", "V030"),
+    ("WND", "270,1,N,0046,1"),
+    ("CIG", "22000,1,9,N"),
+    ("VIS", "016093,1,9,9"),
+    ("TMP", "+0061,1"),
+    ("DEW", "-0044,1"),
+    ("SLP", "10100,1"),
+];
+
+/// The mandatory data groups of daily and monthly summaries, which are all missing.
+const SUMMARY_DEFAULTS: [(&str, &str); 6] = [
+    ("WND", "999,9,9,9999,9"),
+    ("CIG", "99999,9,9,9"),
+    ("VIS", "999999,9,9,9"),
+    ("TMP", "+9999,9"),
+    ("DEW", "+9999,9"),
+    ("SLP", "99999,9"),
+];
+
+/// Builds records from the columns that differ from an ordinary observation. Each row
+/// lists `(column, value)` pairs that override the defaults or add further data groups;
+/// rows of `SOD` and `SOM` reports default to missing mandatory groups.
+pub(crate) fn records(rows: &[&[(&str, &str)]]) -> Vec<Record> {
+    let mut columns: Vec<&str> = DEFAULTS.iter().map(|(column, _)| *column).collect();
+    for (column, _) in rows.iter().flat_map(|row| row.iter()) {
+        if !columns.contains(column) {
+            columns.push(column);
+        }
+    }
+
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(&columns).unwrap();
+    for row in rows {
+        let summary = row
+            .iter()
+            .any(|(column, value)| *column == "REPORT_TYPE" && matches!(*value, "SOD" | "SOM"));
+        let missing: &[(&str, &str)] = if summary { &SUMMARY_DEFAULTS } else { &[] };
+        let value = |column: &str| {
+            row.iter()
+                .chain(missing)
+                .chain(DEFAULTS.iter())
+                .find(|(c, _)| *c == column)
+                .map_or("", |(_, value)| *value)
+        };
+        writer
+            .write_record(columns.iter().map(|column| value(column)))
+            .unwrap();
+    }
+
+    let csv = writer.into_inner().unwrap();
+    csv::Reader::from_reader(csv.as_slice())
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+/// Builds a single record, as [`records`] does.
+pub(crate) fn record(row: &[(&str, &str)]) -> Record {
+    records(&[row]).remove(0)
+}